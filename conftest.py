@@ -1,13 +1,67 @@
 """Configuration parameters for pytest."""
 # pylint: disable=unused-import
+import importlib
+import importlib.abc
+import importlib.util
 import sys
 from os.path import abspath
 from os.path import dirname as d
 from os.path import join
 
+import pytest
 from pytest_mock import mocker
 
 ROOT_DIR = d(d(abspath(__file__)))
 sys.path.insert(0, ROOT_DIR)
 sys.path.insert(0, abspath(join(d(__file__), 'lib')))
+sys.path.insert(0, abspath(join(d(__file__), 'src')))
 print(sys.path)
+
+
+class LibAlias(importlib.abc.MetaPathFinder, importlib.abc.Loader):
+    """Resolve `lib` and `lib.<module>` imports to `blobtools.lib`."""
+
+    def find_spec(self, fullname, path, target=None):
+        if fullname == 'lib' or fullname.startswith('lib.'):
+            return importlib.util.spec_from_loader(fullname, self)
+        return None
+
+    def create_module(self, spec):
+        return importlib.import_module('blobtools.%s' % spec.name)
+
+    def exec_module(self, module):
+        pass
+
+
+sys.meta_path.insert(0, LibAlias())
+
+NODES = [(1, 1, 'no rank'), (2, 1, 'superkingdom'), (3, 2, 'phylum'),
+         (4, 3, 'species'), (5, 1, 'superkingdom'), (6, 5, 'phylum'),
+         (7, 6, 'species')]
+NAMES = {1: 'root', 2: 'Bacteria', 3: 'Proteobacteria', 4: 'Escherichia coli',
+         5: 'Eukaryota', 6: 'Chordata', 7: 'Homo sapiens'}
+
+
+@pytest.fixture(scope='function', name='_taxdump_dir')
+def taxdump_dir(tmp_path):
+    """Write a small NCBI-style taxdump with one merged and one deleted taxid."""
+    directory = tmp_path / 'taxdump'
+    directory.mkdir()
+    parents = {taxid: parent for taxid, parent, _rank in NODES}
+    lineages = []
+    for taxid, _parent, _rank in NODES:
+        lineage = []
+        node = taxid
+        while node != 1:
+            node = parents[node]
+            lineage.insert(0, str(node))
+        lineages.append('%d\t|\t%s \t|' % (taxid, ' '.join(lineage)))
+    (directory / 'nodes.dmp').write_text(''.join(
+        '%d\t|\t%d\t|\t%s\t|\n' % node for node in NODES))
+    (directory / 'names.dmp').write_text(''.join(
+        '%d\t|\t%s\t|\t\t|\tscientific name\t|\n' % name
+        for name in NAMES.items()))
+    (directory / 'taxidlineage.dmp').write_text('\n'.join(lineages) + '\n')
+    (directory / 'merged.dmp').write_text('8\t|\t4\t|\n')
+    (directory / 'delnodes.dmp').write_text('9\t|\n')
+    return str(directory)
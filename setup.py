@@ -139,6 +139,7 @@ setup(
             "pytest-mock>=3.1.1",
             "pytest>=6.0.0",
         ],
+        "bigwig": ["pyBigWig>=0.3.18"],
         "full": ["blobtoolkit-host==4.3.0", "blobtoolkit-pipeline==4.3.5"],
         "host": ["blobtoolkit-host==4.3.0"],
         "pipeline": ["blobtoolkit-pipeline==4.3.5"],
@@ -150,11 +151,14 @@ setup(
         ],
         "blobtools.subcmd": [
             "add = blobtools.lib.add:cli",
+            "chimera = blobtools.lib.chimera:cli",
             "create = blobtools.lib.add:cli",
             "filter = blobtools.lib.filter:cli",
             "host = blobtools.lib.host:cli",
             "remove = blobtools.lib.remove:cli",
             "replace = blobtools.lib.add:cli",
+            "retaxrule = blobtools.lib.retaxrule:cli",
+            "taxdump = blobtools.lib.taxdump_index:cli",
             "validate = blobtools.lib.validate:cli",
             "view = blobtools.lib.view:cli",
         ],
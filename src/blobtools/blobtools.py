@@ -7,11 +7,14 @@ usage: blobtools [<command>] [<args>...] [-h|--help] [--version]
 
 commands:
     add             add data to a BlobDir
+    chimera         find taxonomic switches within sequences
     create          create a new BlobDir
     filter          filter a BlobDir
     host            host interactive view of all BlobDirs in a directory
     replace         call blobtools add with --replace flag
     remove          remove one or more fields from a BlobDir
+    retaxrule       reassign taxonomy using hits stored in a BlobDir
+    taxdump         index a taxdump for faster loading
     validate        validate a BlobDir
     view            generate plots using BlobToolKit Viewer
     -h, --help      show this
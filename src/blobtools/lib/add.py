@@ -7,12 +7,21 @@ Add data to a BlobDir.
 
 Usage:
     blobtools add [--bed BED...] [--beddir DIRECTORY] [--bedtsv TSV...] [--bedtsvdir DIRECTORY]
-                  [--busco TSV...] [--cov BAM...] [--hits TSV...] [--fasta FASTA] [--hits-cols LIST]
+                  [--busco TSV...] [--busco-cov FIELD] [--cov BAM...] [--hits TSV...]
+                  [--fasta FASTA] [--hits-cols LIST]
+                  [--gff GFF...] [--lca TSV...] [--kraken TSV...] [--fcs-gx TXT...]
+                  [--gfa GFA...] [--repeatmasker OUT...] [--vcf VCF...] [--vcf-sample NAME]
                   [--key path=value...] [--link path=url...] [--taxid INT] [--skip-link-test]
                   [--blobdb JSON] [--meta YAML] [--synonyms TSV...] [--trnascan TSV...]
                   [--text TXT...] [--text-delimiter STRING] [--text-cols LIST] [--text-header]
                   [--text-no-array] [--taxdump DIRECTORY] [--taxrule bestsum|bestsumorder[=prefix]]
                   [--threads INT] [--evalue NUMBER] [--bitscore NUMBER] [--hit-count INT]
+                  [--lca-fraction FLOAT] [--tetra] [--tetra-components INT]
+                  [--tetra-min-length INT] [--telomere MOTIF] [--telomere-min-length INT]
+                  [--telomere-window INT]
+                  [--cov-min-mapq INT] [--cov-min-length INT] [--cov-include-flags INT]
+                  [--cov-exclude-flags INT] [--cov-proper-pairs] [--cov-gc-correct]
+                  [--cov-profile]
                   [--update-plot] [--pileup-args key=value...] [--create] [--replace] DIRECTORY
 
 Arguments:
@@ -23,12 +32,66 @@ Options:
     --beddir DIRECTORY    Directory containing one or more BED format files.
     --bedtsv TSV          TSV file with header row and bed-format columns 1-3.
     --bedtsvdir DIRECTORY Directory containing one or more BED-like tsv files.
-    --busco TSV           BUSCO full_table.tsv output file.
-    --cov BAM             BAM/SAM/CRAM read alignment file.
+    --busco TSV           BUSCO or compleasm full_table.tsv output file, or BUSCO v5
+                          short_summary.json alongside its full_table.tsv.
+    --busco-cov FIELD     Coverage field used to separate haplotigs from candidates among
+                          contigs sharing duplicated BUSCOs (e.g. reads_cov). All shorter
+                          partners are reported as candidates if unset.
+    --cov BAM             BAM/SAM/CRAM or PAF read alignment file, mosdepth regions.bed.gz
+                          or mosdepth.summary.txt file, bedGraph or bigWig depth file.
+    --cov-min-mapq INT    Minimum mapping quality of alignments to include in coverage.
+                          [Default: 0]
+    --cov-include-flags INT
+                          Only include BAM/CRAM alignments with all of these SAM flag bits set.
+    --cov-exclude-flags INT
+                          Exclude BAM/CRAM alignments with any of these SAM flag bits set.
+    --cov-proper-pairs    Only include BAM/CRAM alignments in proper pairs.
+    --cov-gc-correct      Fit coverage against GC across windows and add a GC-bias corrected
+                          coverage field for each --cov file. Requires windowed GC and
+                          coverage fields.
+    --cov-profile         Compare base coverage across all libraries in the dataset to add
+                          coverage profile, pairwise log-ratio and embedding fields.
+    --cov-min-length INT  Minimum alignment block length of PAF alignments to include in
+                          coverage. [Default: 0]
     --fasta FASTA         FASTA sequence file.
+    --tetra               Calculate tetranucleotide frequencies when parsing the FASTA
+                          file.
+    --tetra-components INT
+                          Also calculate this number of tetranucleotide principal
+                          components (2 or 3). PCA is slow for large assemblies so is
+                          skipped unless set.
+    --tetra-min-length INT
+                          Minimum sequence length to include when calculating principal
+                          components. Shorter sequences are projected onto the same axes.
+                          [Default: 1000]
+    --telomere MOTIF      Telomeric repeat motif to find at sequence ends when parsing the
+                          FASTA file (e.g. TTAGGG), or 'auto' to use the most common
+                          terminal k-mer.
+    --telomere-min-length INT
+                          Minimum repeat length to count a telomere as present. [Default: 50]
+    --telomere-window INT
+                          Length of sequence to scan at each end. [Default: 20000]
+    --gff GFF             GFF3/GTF gene annotation file. Field IDs are prefixed with
+                          'gff' unless an alternate prefix is specified as GFF=prefix.
     --hits TSV            Tabular BLAST/Diamond output file.
     --hits-cols LIST      Comma separated list of <column number>=<field name>.
                           [Default: 1=qseqid,2=staxids,3=bitscore,5=sseqid,10=qstart,11=qend,14=evalue]
+    --lca TSV             Diamond --outfmt 102 or MMseqs2 easy-taxonomy _lca.tsv file. Field IDs
+                          are prefixed with 'diamond_lca' or 'mmseqs_lca' unless an alternate
+                          prefix is specified as TSV=prefix.
+    --kraken TSV          Kraken2 --output or Centrifuge per-sequence classification file.
+                          Field IDs are prefixed with 'kraken' or 'centrifuge' unless an
+                          alternate prefix is specified as TSV=prefix.
+    --repeatmasker OUT    RepeatMasker (or EarlGrey) .out file. Field IDs are prefixed with
+                          'repeats' unless an alternate prefix is specified as OUT=prefix.
+    --vcf VCF             VCF file of variant calls, optionally gzipped. Field IDs are prefixed
+                          with 'vcf' unless an alternate prefix is specified as VCF=prefix.
+    --vcf-sample NAME     Name of the VCF sample column to use. Defaults to the first sample.
+    --gfa GFA             GFA1 assembly graph file. Segment names are matched to identifiers
+                          and synonyms. Field IDs are prefixed with 'gfa' unless an alternate
+                          prefix is specified as GFA=prefix.
+    --fcs-gx TXT          NCBI FCS-GX fcs_gx_report.txt file. Field IDs are prefixed with
+                          'fcs_gx' unless an alternate prefix is specified as TXT=prefix.
     --taxid INT           Add ranks to metadata for a taxid.
     --key path=value      Set a metadata key to value.
     --link path=URL       Link to an external resource.
@@ -36,15 +99,18 @@ Options:
     --meta YAML           Dataset metadata.
     --blobdb JSON         Blobtools v1 blobDB.
     --synonyms TSV        TSV file containing current identifiers and synonyms.
-    --taxdump DIRECTORY   Location of NCBI new_taxdump directory.
+    --taxdump DIRECTORY   Location of NCBI new_taxdump directory or a GTDB/custom lineage
+                          file of <accession> TAB <d__...;p__...;s__...> rows.
     --taxrule rulename[=prefix]
                           Rule to use when assigning BLAST hits to taxa (bestsum, bestsumorder,
-                          bestdistsum, bestdistsumorder, blastp).
+                          bestdistsum, bestdistsumorder, blastp, lca).
                           An alternate prefix may be specified. [Default: bestsumorder]
     --threads INT         Number of threads to use for multithreaded tasks. [Default: 1]
     --evalue FLOAT        Set evalue cutoff when parsing hits file. [Default: 1]
     --bitscore FLOAT      Set bitscore cutoff when parsing hits file. [Default: 1]
     --hit-count INT       Number of hits to parse when inferring taxonomy. [Default: 10]
+    --lca-fraction FLOAT  Minimum fraction of the top hit bitscore for a hit to be included
+                          when finding the lowest common ancestor (lca taxrule). [Default: 0.9]
     --update-plot         Flag to use new taxrule as default category.
     --text TXT            Generic text file.
     --text-delimiter STRING
@@ -58,6 +124,12 @@ Options:
     --create              Create a new BlobDir.
     --replace             Replace existing fields with matching ids.
 
+BAM/CRAM coverage filtered with --cov-min-mapq, --cov-include-flags, --cov-exclude-flags
+or --cov-proper-pairs is read from samtools view (samtools must be on the PATH) and counts
+the reference bases spanned by each passing alignment, so values are not directly
+comparable with unfiltered coverage. Filters are not applied to windowed coverage and
+cannot be combined with --cov-gc-correct.
+
 Examples:
     # 1. Add BUSCO scores to BlobDir
     blobtools add --busco busco.full_table.tsv BlobDir
@@ -73,14 +145,21 @@ from ..lib import blob_db
 from ..lib import busco
 from ..lib import cov
 from ..lib import fasta
+from ..lib import fcs_gx
 from ..lib import file_io
+from ..lib import gfa
+from ..lib import gff
 from ..lib import hits
 from ..lib import key
+from ..lib import kraken
+from ..lib import lca
 from ..lib import link
+from ..lib import repeatmasker
 from ..lib import synonyms
 from ..lib import taxid
 from ..lib import text
 from ..lib import trnascan
+from ..lib import vcf
 from .fetch import fetch_field
 from .fetch import fetch_metadata
 from .fetch import fetch_taxdump
@@ -94,12 +173,38 @@ FIELDS = [
     {"flag": "--bedtsvdir", "module": bed, "optional": ["identifiers"]},
     {"flag": "--fasta", "module": fasta, "optional": ["identifiers"]},
     {"flag": "--blobdb", "module": blob_db, "depends": ["identifiers"]},
-    {"flag": "--busco", "module": busco, "depends": ["identifiers"]},
+    {"flag": "--busco", "module": busco, "depends": ["identifiers", "length"]},
     {"flag": "--text", "module": text, "depends": ["identifiers"]},
     {"flag": "--trnascan", "module": trnascan, "depends": ["identifiers"]},
+    {"flag": "--gff", "module": gff, "depends": ["identifiers", "length"]},
+    {
+        "flag": "--repeatmasker",
+        "module": repeatmasker,
+        "depends": ["identifiers", "length"],
+    },
     {"flag": "--cov", "module": cov, "depends": ["identifiers", "length", "ncount"]},
-    {"flag": "--hits", "module": hits, "depends": ["identifiers", "length"]},
+    {
+        "flag": "--hits",
+        "module": hits,
+        "depends": ["identifiers", "length"],
+        "taxdump": True,
+    },
+    {
+        "flag": "--lca",
+        "module": lca,
+        "depends": ["identifiers", "length"],
+        "taxdump": True,
+    },
+    {"flag": "--vcf", "module": vcf, "depends": ["identifiers", "length"]},
+    {"flag": "--fcs-gx", "module": fcs_gx, "depends": ["identifiers", "length"]},
+    {
+        "flag": "--kraken",
+        "module": kraken,
+        "depends": ["identifiers", "length"],
+        "taxdump": True,
+    },
     {"flag": "--synonyms", "module": synonyms, "depends": ["identifiers"]},
+    {"flag": "--gfa", "module": gfa, "depends": ["identifiers"]},
 ]
 PARAMS = set(
     ["--taxrule", "--threads", "--pileup-args", "--evalue", "--bitscore", "--hit-count"]
@@ -120,6 +225,9 @@ def has_field_warning(meta, field_id):
 
 def main(args):
     """Entrypoint for blobtools add."""
+    if args["--cov-gc-correct"] and not args["--cov"]:
+        print("ERROR: --cov-gc-correct requires --cov to set the coverage to correct")
+        sys.exit(1)
     meta = fetch_metadata(args["DIRECTORY"], **args)
     if args["--fasta"]:
         meta.assembly.update({"file": args["--fasta"]})
@@ -138,7 +246,7 @@ def main(args):
                         "ERROR: You may need to rebuild the BlobDir to run this command."
                     )
                     sys.exit(1)
-            if field["flag"] == "--hits":
+            if field.get("taxdump"):
                 if not taxdump:
                     taxdump = fetch_taxdump(args["--taxdump"])
             parents = field["module"].parent()
@@ -154,6 +154,57 @@ def field_settings():
                 "children",
             ],
         },
+        "gaps": {
+            "meta": {
+                "name": "N-gap count",
+                "scale": "scaleLinear",
+                "datatype": "integer",
+                "type": "variable",
+            },
+            "parents": [
+                {
+                    "id": "gaps_data",
+                    "scale": "scaleLinear",
+                    "datatype": "integer",
+                    "type": "variable",
+                },
+                "children",
+            ],
+        },
+        "gapspan": {
+            "meta": {
+                "name": "N-gap span",
+                "scale": "scaleLinear",
+                "datatype": "integer",
+                "type": "variable",
+            },
+            "parents": [
+                {
+                    "id": "gapspan_data",
+                    "scale": "scaleLinear",
+                    "datatype": "integer",
+                    "type": "variable",
+                },
+                "children",
+            ],
+        },
+        "iupac": {
+            "meta": {
+                "name": "IUPAC count",
+                "scale": "scaleLinear",
+                "datatype": "integer",
+                "type": "variable",
+            },
+            "parents": [
+                {
+                    "id": "iupac_data",
+                    "scale": "scaleLinear",
+                    "datatype": "integer",
+                    "type": "variable",
+                },
+                "children",
+            ],
+        },
         "ncount": {
             "meta": {
                 "name": "N count",
@@ -371,6 +422,103 @@ def get_range(values):
     return value_range
 
 
+def window_setting(value):
+    """Describe a window size setting by its key, value and field title."""
+    key = ("%f" % float(value)).rstrip("0").rstrip(".")
+    return {
+        "key": key,
+        "value": float(value),
+        "title": "windows" if key == "0.1" else "windows_%s" % key,
+    }
+
+
+def list_windows(meta):
+    """List window sizes from dataset metadata."""
+    try:
+        stats_windows = meta.settings.get("stats_windows", [0.1])
+    except AttributeError:
+        stats_windows = [0.1]
+    if not isinstance(stats_windows, list):
+        stats_windows = [stats_windows]
+    return [window_setting(x) for x in stats_windows if float(x) != 1]
+
+
+def window_size(length, window):
+    """Calculate window size for a sequence of a given length."""
+    if window > 1:
+        return int(window)
+    if window == 1 or length <= 1000:
+        return max(length, 1)
+    return round(length * window / 1000 + 0.5) * 1000
+
+
+def window_starts(length, size):
+    """List window start positions along a sequence."""
+    return list(range(0, max(length, 1), size))
+
+
+def window_spans(length, size):
+    """List the number of bases in each window along a sequence."""
+    return [min(size, length - start) for start in window_starts(length, size)]
+
+
+def fraction(bases, span):
+    """Calculate a fraction to 4 decimal places."""
+    return float("%.4f" % (bases / span)) if span else 0
+
+
+def window_fractions(totals, length, size):
+    """Divide per-window totals by the number of bases in each window."""
+    return [
+        fraction(total, span)
+        for total, span in zip(totals, window_spans(length, size))
+    ]
+
+
+def intervals_to_windows(intervals, length, size):
+    """Count bases covered by (1-based, inclusive) intervals in each window."""
+    covered = [0] * len(window_starts(length, size))
+    for start, end in intervals:
+        start = max(start - 1, 0)
+        end = min(end, length)
+        while start < end:
+            index = start // size
+            if index >= len(covered):
+                break
+            window_end = min((index + 1) * size, end)
+            covered[index] += window_end - start
+            start = window_end
+    return covered
+
+
+def merge_intervals(intervals):
+    """Merge overlapping (1-based, inclusive) intervals."""
+    merged = []
+    for start, end in sorted(intervals):
+        if merged and start <= merged[-1][1] + 1:
+            merged[-1][1] = max(merged[-1][1], end)
+        else:
+            merged.append([start, end])
+    return merged
+
+
+def windows_field(field_id, name, window, values, parents):
+    """Create a MultiArray of windowed values following the BED import layout."""
+    windows_id = "%s_%s" % (field_id, window["title"])
+    return MultiArray(
+        windows_id,
+        meta={
+            "field_id": windows_id,
+            "name": "%s windows %s" % (name, window["key"]),
+            "type": "multiarray",
+            "datatype": "mixed",
+        },
+        values=[[[value] for value in seq_values] for seq_values in values],
+        parents=parents,
+        headers=[field_id],
+    )
+
+
 def parse(files, **kwargs):
     if "--bedtsvdir" in kwargs or "--bedtsvdir" in kwargs:
         if isinstance(files, str) and path.isdir(files):
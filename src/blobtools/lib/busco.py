@@ -1,54 +1,188 @@
 #!/usr/bin/env python3
 """Parse BUSCO results into MultiArray Field."""
 
+import pathlib
 import re
+import sys
+from collections import Counter
 from collections import defaultdict
 
 from ..lib import file_io
+from .bed import list_windows
+from .bed import window_size
+from .bed import window_starts
+from .fetch import fetch_field
+from .field import Category
 from .field import MultiArray
+from .field import Variable
+
+MIN_SHARED = 2
+
+STATUSES = {
+    "Single": "Complete",
+    "Incomplete": "Fragmented",
+    "Interspersed": "Fragmented",
+}
+
+HEADERS = ("Busco id", "Status", "Start", "End", "Strand", "Score")
+
+LOCATION_COLUMNS = {
+    "start": ("Gene Start", "Start"),
+    "end": ("Gene End", "End"),
+    "strand": ("Strand",),
+    "score": ("Score",),
+}
+
+
+def lineage_from_path(busco_file):
+    """Find a lineage dataset name in a file path or an adjacent summary file."""
+    path = pathlib.Path(busco_file)
+    summary = file_io.read_file(str(path.parent / "summary.txt"))
+    if summary:
+        match = re.search(r"lineage:\s*(\w+)", summary)
+        if match:
+            return match[1]
+    for part in reversed(path.parts[:-1]):
+        match = re.search(r"(\w+_odb\d+)", part)
+        if match:
+            return match[1]
+    return None
+
+
+def parse_busco_header(lines, busco_file):
+    """Parse version, lineage and column names from BUSCO table comment lines."""
+    meta = {"file": busco_file}
+    columns = []
+    for line in lines:
+        if not line.startswith("#"):
+            break
+        if "BUSCO version is:" in line:
+            meta["version"] = line.split(":", 1)[1].strip()
+        elif "lineage dataset is:" in line:
+            desc = re.split(r":\s*|\(|\)\s*|,\s*", line)
+            meta["set"] = desc[1].strip()
+            match = re.search(r"number of BUSCOs:\s*(\d+)", line)
+            if match:
+                meta["count"] = int(match[1])
+        elif "To reproduce this run" in line:
+            match = re.search(r"-l\s.*?\/*(\w+_odb\d+)\/", line)
+            if match:
+                meta["set"] = match[1]
+        elif line.startswith("# Busco id"):
+            columns = re.split(r"# |\t", line)[1:]
+    rows = [re.split("\t", line) for line in lines if not line.startswith("#")]
+    return meta, columns, rows
+
+
+def parse_compleasm_table(lines, busco_file):
+    """Parse a compleasm full_table.tsv into BUSCO table columns and rows."""
+    columns = ["Busco id" if col == "Gene" else col for col in lines[0].split("\t")]
+    meta = {"file": busco_file, "version": "compleasm"}
+    rows = [re.split("\t", line) for line in lines[1:]]
+    return meta, columns, rows
+
+
+def parse_busco_summary(summary_file):
+    """Parse a BUSCO v5 short_summary.json and locate the matching full table."""
+    summary = file_io.load_yaml(summary_file)
+    if not summary:
+        print("WARNING file %s is empty" % summary_file)
+        return None, None
+    lineage = summary.get("lineage_dataset", {})
+    meta = {
+        "version": summary.get("versions", {}).get("busco"),
+        "set": lineage.get("name"),
+        "count": int(lineage.get("number_of_buscos", 0)),
+        "summary": summary.get("results", {}).get("one_line_summary"),
+    }
+    meta = {key: value for key, value in meta.items() if value}
+    directory = pathlib.Path(summary_file).parent
+    for table in (
+        directory / "full_table.tsv",
+        directory / ("run_%s" % meta.get("set")) / "full_table.tsv",
+    ):
+        if table.is_file():
+            return str(table), meta
+    raise UserWarning(
+        "Unable to find a full_table.tsv file to accompany %s." % summary_file
+    )
+
+
+def gene_location(row, columns, region=None):
+    """
+    Parse gene start, end, strand and score from a BUSCO table row.
+
+    Falls back to the region in a sequence name suffix when a table has no
+    usable gene coordinates.
+    """
+    location = {}
+    for key, names in LOCATION_COLUMNS.items():
+        for name in names:
+            if name in columns and len(row) > columns.index(name):
+                location[key] = row[columns.index(name)]
+                break
+    try:
+        start, end = int(location["start"]), int(location["end"])
+    except (KeyError, ValueError):
+        start, end = region or (0, 0)
+    try:
+        score = float(location["score"])
+    except (KeyError, ValueError):
+        score = 0
+    return [min(start, end), max(start, end), location.get("strand") or ".", score]
 
 
 def parse_busco(busco_file, identifiers):  # pylint: disable=too-many-locals
     """Parse BUSCO results into a MultiArray."""
+    summary = {}
+    if busco_file.endswith(".json"):
+        busco_file, summary = parse_busco_summary(busco_file)
+        if busco_file is None:
+            return None
     data = file_io.read_file(busco_file)
     if not data:
         print("WARNING file %s is empty" % busco_file)
         return None
-    lines = data.split("\n")
-    version = lines[0].split(":")[1].strip()
-    desc = re.split(r":\s*|\(|\)\s*|,\s*", lines[1])
-    meta = {
-        "version": version,
-        "set": desc[1].strip(),
-        "count": int(desc[7].strip()),
-        "file": busco_file,
-    }
-    version = int(version.split(".")[0])
-    if version < 4:
-        rows = [re.split("\t", line) for line in lines[5:]]
-        meta["set"] = re.search(
-            r"-l\s.*?\/*(\w+_odb\d+)\/", lines[2].split(":")[1].strip()
-        )[1]
-        columns = re.split(r"# |\t", lines[4])[1:]
-        try:
-            contig_index = columns.index("Contig")
-        except ValueError:
-            contig_index = columns.index("Sequence")
+    lines = [line for line in data.split("\n") if line]
+    if lines[0].startswith("#"):
+        meta, columns, rows = parse_busco_header(lines, busco_file)
     else:
-        rows = [re.split("\t", line) for line in lines[3:]]
-        columns = re.split(r"# |\t", lines[2])[1:]
-        contig_index = columns.index("Sequence")
+        meta, columns, rows = parse_compleasm_table(lines, busco_file)
+    meta.update(summary)
+    if "set" not in meta:
+        meta["set"] = lineage_from_path(busco_file)
+        if meta["set"] is None:
+            raise UserWarning(
+                "Unable to determine the BUSCO lineage for %s." % busco_file
+            )
+    try:
+        busco_index = columns.index("Busco id")
+        status_index = columns.index("Status")
+    except ValueError as err:
+        raise UserWarning(
+            "Unable to find BUSCO table columns in %s." % busco_file
+        ) from err
+    if "count" not in meta:
+        meta["count"] = len({row[busco_index] for row in rows})
+    meta.setdefault("version", "unknown")
+    version = re.match(r"\d+", meta["version"])
+    version = int(version[0]) if version else 5
+    contig_index = columns.index("Contig" if "Contig" in columns else "Sequence")
     meta["field_id"] = "%s_busco" % meta["set"]
-    busco_index = columns.index("Busco id")
-    status_index = columns.index("Status")
     results = defaultdict(list)
     for row in rows:
-        if len(row) > contig_index:
-            if version < 4:
-                contig = row[contig_index]
+        if len(row) > contig_index and row[contig_index]:
+            status = STATUSES.get(row[status_index], row[status_index])
+            contig = row[contig_index]
+            region = re.search(r":(\d+)-(\d+)$", contig)
+            if version >= 4 and region:
+                contig = contig[: region.start()]
+                region = (int(region[1]), int(region[2]))
             else:
-                contig = re.sub(r":\d+-\d+$", "", row[contig_index])
-            results[contig].append([row[busco_index], row[status_index]])
+                region = None
+            results[contig].append(
+                [row[busco_index], status, *gene_location(row, columns, region)]
+            )
     if not identifiers.validate_list(list(results.keys())):
         # try removing _\d+ suffix added by prokka-based busco
         res = {}
@@ -68,20 +202,173 @@ def parse_busco(busco_file, identifiers):  # pylint: disable=too-many-locals
         meta["field_id"],
         values=values,
         meta=meta,
-        headers=("Busco id", "Status"),
+        headers=HEADERS,
         parents=["children"],
         category_slot=1,
     )
     return busco_field
 
 
+def gene_window(start, end, size, count):
+    """Find the index of the window containing a gene midpoint."""
+    return min(max((start + end) // 2 - 1, 0) // size, count - 1)
+
+
+def busco_window_fields(busco_field, lengths, windows):
+    """Create MultiArrays assigning each located BUSCO gene to a window."""
+    fields = []
+    values = busco_field.expand_values()
+    for window in windows:
+        window_values = []
+        for buscos, length in zip(values, lengths):
+            size = window_size(length, window["value"])
+            count = len(window_starts(length, size))
+            window_values.append(
+                [
+                    [busco[0], busco[1], gene_window(busco[2], busco[3], size, count)]
+                    for busco in buscos
+                    if busco[2]
+                ]
+            )
+        field_id = "%s_%s" % (busco_field.field_id, window["title"])
+        fields.append(
+            MultiArray(
+                field_id,
+                values=window_values,
+                meta={
+                    "field_id": field_id,
+                    "name": "%s windows %s" % (busco_field.field_id, window["key"]),
+                    "datatype": "mixed",
+                    "preload": False,
+                    "active": False,
+                },
+                headers=("Busco id", "Status", "Window"),
+                parents=["children"],
+                category_slot=1,
+            )
+        )
+    return fields
+
+
+def shared_duplicates(values, identifiers):
+    """Count Duplicated BUSCOs shared between each pair of contigs."""
+    by_busco = defaultdict(set)
+    for seq_id, buscos in zip(identifiers.values, values):
+        for busco in buscos:
+            if busco[1] == "Duplicated":
+                by_busco[busco[0]].add(seq_id)
+    shared = defaultdict(Counter)
+    for seq_ids in by_busco.values():
+        for seq_id in seq_ids:
+            for partner in seq_ids:
+                if partner != seq_id:
+                    shared[seq_id][partner] += 1
+    return shared
+
+
+def haplotig_status(seq_id, partner, lengths, covs):
+    """
+    Classify a contig relative to its duplicate BUSCO partner.
+
+    The shorter contig of a pair is a likely haplotig when both have similar
+    coverage, or a candidate when coverage differs or is unavailable. Contigs of
+    equal length are ranked by coverage and then by identifier.
+    """
+    rank = (lengths[seq_id], covs[seq_id] if covs else 0)
+    partner_rank = (lengths[partner], covs[partner] if covs else 0)
+    if rank > partner_rank or (rank == partner_rank and seq_id < partner):
+        return "primary"
+    if covs is None:
+        return "candidate"
+    if covs[partner] and 0.5 <= covs[seq_id] / covs[partner] <= 2:
+        return "haplotig"
+    return "candidate"
+
+
+def haplotig_fields(busco_field, identifiers, lengths, covs=None):
+    """Create duplicate partner, shared count and haplotig Fields."""
+    shared = shared_duplicates(busco_field.expand_values(), identifiers)
+    if not shared:
+        return []
+    lengths = dict(zip(identifiers.values, lengths))
+    if covs is not None:
+        covs = dict(zip(identifiers.values, covs))
+    prefix = busco_field.field_id.replace("_busco", "")
+    partners = []
+    counts = []
+    statuses = []
+    for seq_id in identifiers.values:
+        partner, count = (shared[seq_id].most_common(1) or [("none", 0)])[0]
+        if count < MIN_SHARED:
+            partner, count = "none", 0
+        partners.append(partner)
+        counts.append(count)
+        statuses.append(
+            haplotig_status(seq_id, partner, lengths, covs) if count else "none"
+        )
+    meta = {"preload": False, "active": False, "min_shared": MIN_SHARED}
+    return [
+        Category(
+            "%s_duplicate_partner" % prefix,
+            values=partners,
+            meta={
+                **meta,
+                "field_id": "%s_duplicate_partner" % prefix,
+                "name": "%s duplicate partner" % prefix,
+            },
+            parents=["children"],
+        ),
+        Variable(
+            "%s_duplicate_shared" % prefix,
+            values=counts,
+            meta={
+                **meta,
+                "field_id": "%s_duplicate_shared" % prefix,
+                "name": "%s shared duplicates" % prefix,
+                "scale": "scaleLinear",
+                "datatype": "integer",
+                "range": [min(counts), max(counts)],
+            },
+            parents=["children"],
+        ),
+        Category(
+            "%s_haplotig" % prefix,
+            values=statuses,
+            meta={
+                **meta,
+                "field_id": "%s_haplotig" % prefix,
+                "name": "%s haplotig" % prefix,
+            },
+            parents=["children"],
+        ),
+    ]
+
+
 def parse(files, **kwargs):
     """Parse all BUSCO files."""
     parsed = []
+    identifiers = kwargs["dependencies"]["identifiers"]
+    meta = kwargs["meta"]
+    lengths = kwargs["dependencies"]["length"]
+    covs = None
+    if kwargs.get("--busco-cov"):
+        covs = fetch_field(kwargs["DIRECTORY"], kwargs["--busco-cov"], meta)
+        if not covs:
+            print(
+                "ERROR: '%s.json' was not found in the BlobDir." % kwargs["--busco-cov"]
+            )
+            sys.exit(1)
     for file in files:
-        busco = parse_busco(file, identifiers=kwargs["dependencies"]["identifiers"])
+        busco = parse_busco(file, identifiers=identifiers)
         if busco is not None:
             parsed.append(busco)
+            parsed += busco_window_fields(busco, lengths.values, list_windows(meta))
+            parsed += haplotig_fields(
+                busco,
+                identifiers,
+                lengths.values,
+                covs.values if covs else None,
+            )
     return parsed
 
 
@@ -0,0 +1,381 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""
+Find contigs with taxonomic switches between windows.
+
+Usage:
+    blobtools chimera [--taxonomy FIELD] [--cov FIELD] [--window FLOAT]
+                      [--min-windows INT] [--min-gc-shift FLOAT]
+                      [--min-cov-ratio FLOAT] [--bed BED] [--report TSV]
+                      [--replace] DIRECTORY
+
+Arguments:
+    DIRECTORY              Existing Blob directory.
+
+Options:
+    --taxonomy FIELD       Taxonomy category field with windowed values
+                           (e.g. bestsumorder_phylum). Defaults to the current plot
+                           category.
+    --cov FIELD            Coverage field with windowed values. Defaults to the current
+                           plot y-axis field.
+    --window FLOAT         Window size setting for GC and coverage values.
+                           [Default: 0.1]
+    --min-windows INT      Minimum number of windows assigned to each taxon either side
+                           of a switch. [Default: 2]
+    --min-gc-shift FLOAT   Minimum GC difference between taxa to support a switch.
+                           [Default: 0.05]
+    --min-cov-ratio FLOAT  Minimum coverage fold-change between taxa to support a
+                           switch. [Default: 1.5]
+    --bed BED              Output BED file of suspect regions around each switch.
+    --report TSV           Output file for the breakpoint report. Defaults to STDOUT.
+    --replace              Replace existing fields with matching ids.
+
+The finest windowed taxonomy available for each sequence is scanned and a switch is
+reported when runs of at least --min-windows windows are assigned to different taxa.
+Windows with no hits are ignored and shorter runs are merged into their neighbours.
+Each switch is marked as supported by GC and/or coverage when the mean values of the
+windows either side differ by the given thresholds.
+
+Examples:
+    # 1. Report taxonomic switches at phylum level
+    blobtools chimera --taxonomy bestsumorder_phylum --bed suspect.bed BlobDir
+
+"""
+
+import re
+import sys
+
+from docopt import docopt
+
+from ..lib import file_io
+from .add import has_field_warning
+from .bed import list_windows
+from .bed import window_size
+from .fetch import fetch_field
+from .fetch import fetch_metadata
+from .field import Category
+from .field import Variable
+from .hits import bin_size
+from .version import __version__
+
+UNASSIGNED = (None, "no-hit", "undef", "unresolved")
+
+
+def category_runs(categories, size, length, min_windows):
+    """Collapse windowed categories into runs of at least min_windows windows."""
+    runs = []
+    for index, category in enumerate(categories):
+        if category in UNASSIGNED:
+            continue
+        start = index * size
+        end = min(start + size, length)
+        if runs and runs[-1]["category"] == category:
+            runs[-1].update({"end": end, "last_start": start})
+            runs[-1]["count"] += 1
+            continue
+        runs.append(
+            {
+                "category": category,
+                "count": 1,
+                "start": start,
+                "end": end,
+                "last_start": start,
+                "first_end": end,
+            }
+        )
+    merged = []
+    for run in runs:
+        if run["count"] < min_windows:
+            continue
+        if merged and merged[-1]["category"] == run["category"]:
+            merged[-1].update({"end": run["end"], "last_start": run["last_start"]})
+            merged[-1]["count"] += run["count"]
+            continue
+        merged.append(run)
+    return merged
+
+
+def run_mean(values, size, length, run):
+    """Calculate the mean of windowed values with midpoints inside a run."""
+    selected = []
+    for index, value in enumerate(values):
+        midpoint = (index * size + min((index + 1) * size, length)) // 2
+        if value is not None and run["start"] <= midpoint < run["end"]:
+            selected.append(value)
+    if not selected:
+        return None
+    return sum(selected) / len(selected)
+
+
+def switch_support(before, after, min_gc_shift, min_cov_ratio):
+    """List the windowed statistics supporting a taxonomic switch."""
+    support = []
+    if before["gc"] is not None and after["gc"] is not None:
+        if abs(before["gc"] - after["gc"]) >= min_gc_shift:
+            support.append("gc")
+    if before["cov"] is not None and after["cov"] is not None:
+        low, high = sorted([before["cov"], after["cov"]])
+        if high and (not low or high / low >= min_cov_ratio):
+            support.append("cov")
+    return support
+
+
+def find_switches(seq_id, length, taxonomy, stats, **kwargs):
+    """Find taxonomic switches and estimated breakpoints along a sequence."""
+    categories, size = taxonomy
+    runs = category_runs(categories, size, length, kwargs["min_windows"])
+    for run in runs:
+        for key in ("gc", "cov"):
+            run[key] = None
+            if key in stats:
+                run[key] = run_mean(*stats[key], length, run)
+    switches = []
+    for before, after in zip(runs, runs[1:]):
+        switches.append(
+            {
+                "seq_id": seq_id,
+                "breakpoint": (before["end"] + after["start"]) // 2,
+                "start": before["last_start"],
+                "end": after["first_end"],
+                "before": before,
+                "after": after,
+                "support": switch_support(
+                    before, after, kwargs["min_gc_shift"], kwargs["min_cov_ratio"]
+                ),
+            }
+        )
+    return switches
+
+
+def switch_status(switches):
+    """Summarise the switches along a sequence as a category."""
+    if not switches:
+        return "none"
+    support = {key for switch in switches for key in switch["support"]}
+    return "+".join(["taxonomy"] + [key for key in ("gc", "cov") if key in support])
+
+
+def format_value(value):
+    """Format an optional windowed mean for the report."""
+    return "NA" if value is None else "%.4g" % value
+
+
+def report_rows(switches):
+    """Create breakpoint report rows."""
+    rows = [
+        [
+            "identifier",
+            "breakpoint",
+            "start",
+            "end",
+            "before",
+            "after",
+            "gc_before",
+            "gc_after",
+            "cov_before",
+            "cov_after",
+            "support",
+        ]
+    ]
+    for switch in switches:
+        rows.append(
+            [
+                switch["seq_id"],
+                switch["breakpoint"],
+                switch["start"],
+                switch["end"],
+                switch["before"]["category"],
+                switch["after"]["category"],
+                format_value(switch["before"]["gc"]),
+                format_value(switch["after"]["gc"]),
+                format_value(switch["before"]["cov"]),
+                format_value(switch["after"]["cov"]),
+                ",".join(switch["support"]) or "none",
+            ]
+        )
+    return rows
+
+
+def bed_lines(switches):
+    """Create BED lines for suspect regions around each switch."""
+    return [
+        "%s\t%d\t%d\t%s>%s"
+        % (
+            switch["seq_id"],
+            switch["start"],
+            switch["end"],
+            switch["before"]["category"],
+            switch["after"]["category"],
+        )
+        for switch in switches
+    ] + [""]
+
+
+def fetch_windows(directory, field_id, meta, title):
+    """Fetch expanded windowed values for a field if available."""
+    windows_id = "%s_%s" % (field_id, title)
+    if not field_id or not meta.has_field(windows_id):
+        print("WARN: '%s' was not found in the BlobDir." % windows_id, file=sys.stderr)
+        return None
+    field = fetch_field(directory, windows_id, meta)
+    if field is None:
+        return None
+    return [
+        [value[0] if value else None for value in seq_values]
+        for seq_values in field.expand_values()
+    ]
+
+
+def fetch_taxonomy_windows(directory, field_id, meta):
+    """Fetch windowed taxonomy values for all window sizes keyed by window size."""
+    windows = {}
+    for windows_id in meta.list_fields():
+        pattern = r"^%s_(windows(?:_([\d.]+))?)$" % re.escape(field_id)
+        match = re.match(pattern, windows_id)
+        if match is None:
+            continue
+        window = float(match[2]) if match[2] else 0.1
+        windows[window] = fetch_windows(directory, field_id, meta, match[1])
+    return {window: values for window, values in windows.items() if values}
+
+
+def finest_taxonomy(windows, index, length):
+    """Choose the windowed taxonomy with the most bins for a sequence."""
+    best = ([], length)
+    for window, values in windows.items():
+        if len(values[index]) > len(best[0]):
+            best = (values[index], bin_size(length, window))
+    return best
+
+
+def chimera_fields(statuses, counts, meta):
+    """Create chimera status and switch count Fields."""
+    parents = [
+        {
+            "id": "chimera",
+            "name": "Chimera",
+            "datatype": "string",
+            "type": "category",
+        },
+        "children",
+    ]
+    return [
+        Category(
+            "chimera_status",
+            values=statuses,
+            meta={**meta, "field_id": "chimera_status", "name": "Chimera status"},
+            parents=parents,
+        ),
+        Variable(
+            "chimera_switches",
+            values=counts,
+            meta={
+                **meta,
+                "field_id": "chimera_switches",
+                "name": "Taxonomic switches",
+                "scale": "scaleLinear",
+                "datatype": "integer",
+                "range": [min(counts), max(counts)],
+            },
+            parents=parents,
+        ),
+    ]
+
+
+def main(args):
+    """Entrypoint for blobtools chimera."""
+    meta = fetch_metadata(args["DIRECTORY"], **args)
+    taxonomy = args["--taxonomy"] or meta.plot.get("cat")
+    cov = args["--cov"] or meta.plot.get("y")
+    window = float(args["--window"])
+    title = next(
+        (obj["title"] for obj in list_windows(meta) if obj["value"] == window), None
+    )
+    if title is None:
+        print(
+            "ERROR: Window size %s is not set for this BlobDir." % args["--window"],
+            file=sys.stderr,
+        )
+        sys.exit(1)
+    identifiers = fetch_field(args["DIRECTORY"], "identifiers", meta)
+    lengths = fetch_field(args["DIRECTORY"], "length", meta)
+    taxonomy_windows = fetch_taxonomy_windows(args["DIRECTORY"], taxonomy, meta)
+    if not taxonomy_windows:
+        print(
+            "ERROR: No windowed values were found for '%s'." % taxonomy,
+            file=sys.stderr,
+        )
+        sys.exit(1)
+    stats_windows = {
+        key: fetch_windows(args["DIRECTORY"], field_id, meta, title)
+        for key, field_id in (("gc", "gc"), ("cov", cov))
+    }
+    settings = {
+        "min_windows": int(args["--min-windows"]),
+        "min_gc_shift": float(args["--min-gc-shift"]),
+        "min_cov_ratio": float(args["--min-cov-ratio"]),
+    }
+    switches = []
+    statuses = []
+    counts = []
+    for index, seq_id in enumerate(identifiers.values):
+        length = lengths.values[index]
+        size = window_size(length, window)
+        seq_switches = find_switches(
+            seq_id,
+            length,
+            finest_taxonomy(taxonomy_windows, index, length),
+            {
+                key: (values[index], size)
+                for key, values in stats_windows.items()
+                if values
+            },
+            **settings,
+        )
+        switches += seq_switches
+        statuses.append(switch_status(seq_switches))
+        counts.append(len(seq_switches))
+    print(
+        "Found %d taxonomic switches in %d sequences"
+        % (len(switches), len([count for count in counts if count])),
+        file=sys.stderr,
+    )
+    lines = ["\t".join(str(value) for value in row) for row in report_rows(switches)]
+    if args["--report"]:
+        file_io.write_file(args["--report"], lines + [""], plain=True)
+    else:
+        print("\n".join(lines))
+    if args["--bed"]:
+        file_io.write_file(args["--bed"], bed_lines(switches), plain=True)
+    field_meta = {
+        **settings,
+        "taxonomy": taxonomy,
+        "window": window,
+        "preload": False,
+        "active": False,
+    }
+    if stats_windows["cov"] is not None:
+        field_meta["cov"] = cov
+    for data in chimera_fields(statuses, counts, field_meta):
+        if not args["--replace"]:
+            if has_field_warning(meta, data.field_id):
+                continue
+        meta.add_field(data.parents, **data.meta)
+        json_file = "%s/%s.json" % (args["DIRECTORY"], data.field_id)
+        file_io.write_file(json_file, data.values_to_dict())
+    file_io.write_file("%s/meta.json" % args["DIRECTORY"], meta.to_dict())
+
+
+def cli():
+    """Entry point."""
+    if len(sys.argv) == sys.argv.index(__name__.split(".")[-1]) + 1:
+        args = docopt(__doc__, argv=[])
+    else:
+        args = docopt(__doc__, version=__version__)
+    main(args)
+
+
+if __name__ == "__main__":
+    cli()
@@ -7,17 +7,44 @@
 
 import math
 import os
+import re
 import sys
 from collections import defaultdict
 from pathlib import Path
+from statistics import median
+from subprocess import PIPE
+from subprocess import Popen
 
 from blobtk import depth
 from blobtk import filter
 from tqdm import tqdm
 
+from ..lib import file_io
+from .bed import list_windows
+from .bed import window_fractions
+from .bed import window_size
+from .bed import window_spans
+from .bed import window_starts
+from .bed import windows_field
+from .cov_profile import profile_fields
+from .fetch import fetch_field
 from .field import Variable
 from .file_io import load_yaml
 
+DEPTH_SUFFIXES = {
+    ".mosdepth.summary.txt": "summary",
+    ".regions.bed.gz": "intervals",
+    ".regions.bed": "intervals",
+    ".bedgraph.gz": "intervals",
+    ".bedgraph": "intervals",
+    ".bg.gz": "intervals",
+    ".bg": "intervals",
+    ".bigwig": "bigwig",
+    ".bw": "bigwig",
+    ".paf.gz": "paf",
+    ".paf": "paf",
+}
+
 
 def get_coverage(bam_file):
     """Get base coverage."""
@@ -25,45 +52,226 @@ def get_coverage(bam_file):
     return {cov.seq_name: cov.bins[0] for cov in binned_covs}
 
 
+def filter_settings(**kwargs):
+    """Read alignment filter settings from command line options."""
+    include_flags = int(kwargs.get("--cov-include-flags") or 0)
+    if kwargs.get("--cov-proper-pairs"):
+        include_flags |= 2
+    return {
+        "min_mapq": int(kwargs.get("--cov-min-mapq") or 0),
+        "include_flags": include_flags,
+        "exclude_flags": int(kwargs.get("--cov-exclude-flags") or 0),
+        "proper_pairs": bool(kwargs.get("--cov-proper-pairs")),
+    }
+
+
+def reference_length(cigar):
+    """Count reference bases covered by an alignment CIGAR string."""
+    return sum(
+        int(length)
+        for length, operation in re.findall(r"(\d+)([MIDNSHP=X])", cigar)
+        if operation in "MD=X"
+    )
+
+
+def get_filtered_coverage(bam_file, settings):
+    """Get base coverage from alignments passing MAPQ and flag filters."""
+    cmd = ["samtools", "view", "-q", str(settings["min_mapq"])]
+    if settings["include_flags"]:
+        cmd += ["-f", str(settings["include_flags"])]
+    if settings["exclude_flags"]:
+        cmd += ["-F", str(settings["exclude_flags"])]
+    cmd.append(bam_file)
+    aligned = defaultdict(int)
+    try:
+        with Popen(cmd, stdout=PIPE, encoding="utf-8", bufsize=4096) as proc:
+            for line in proc.stdout:
+                row = line.split("\t", 6)
+                if len(row) < 6 or row[2] == "*":
+                    continue
+                aligned[row[2]] += reference_length(row[5])
+    except FileNotFoundError:
+        print("ERROR: samtools is required to filter alignments for coverage")
+        sys.exit(1)
+    return aligned
+
+
+def depth_format(filename):
+    """Identify a depth file format from its suffix."""
+    for suffix, file_format in DEPTH_SUFFIXES.items():
+        if filename.lower().endswith(suffix):
+            return suffix, file_format
+    return None, None
+
+
+def parse_depth_intervals(depth_file):
+    """Parse mosdepth regions or bedGraph intervals."""
+    intervals = defaultdict(list)
+    for line in file_io.stream_file(depth_file):
+        if line.startswith(("#", "track", "browser")):
+            continue
+        row = line.rstrip("\n").split("\t")
+        if len(row) < 4:
+            continue
+        try:
+            intervals[row[0]].append((int(row[1]), int(row[2]), float(row[-1])))
+        except ValueError:
+            continue
+    return intervals
+
+
+def parse_bigwig(depth_file):
+    """Parse bigWig intervals."""
+    try:
+        import pyBigWig
+    except ImportError:
+        print(
+            "ERROR: pyBigWig is required to read bigWig coverage files, "
+            "install it with 'pip install blobtoolkit[bigwig]'"
+        )
+        sys.exit(1)
+    bw = pyBigWig.open(depth_file)
+    intervals = {}
+    for seq_id in bw.chroms():
+        intervals[seq_id] = [
+            (start, end, value) for start, end, value in bw.intervals(seq_id) or []
+        ]
+    bw.close()
+    return intervals
+
+
+def parse_mosdepth_summary(depth_file):
+    """Parse per-sequence mean coverage from a mosdepth summary file."""
+    covs = {}
+    for line in file_io.stream_file(depth_file):
+        row = line.rstrip("\n").split("\t")
+        if row[0] in ("chrom", "total") or row[0].endswith("_region"):
+            continue
+        try:
+            covs[row[0]] = float(row[3])
+        except (IndexError, ValueError):
+            continue
+    return covs
+
+
+def parse_paf_file(paf_file, min_mapq=0, min_length=0):
+    """Parse primary alignment intervals and read names from a PAF file."""
+    intervals = defaultdict(list)
+    reads = defaultdict(set)
+    counts = {"alignments": 0, "filtered": 0}
+    for line in file_io.stream_file(paf_file):
+        row = line.rstrip("\n").split("\t")
+        if len(row) < 12:
+            continue
+        if "tp:A:S" in row[12:] or "tp:A:i" in row[12:]:
+            continue
+        counts["alignments"] += 1
+        if int(row[11]) < min_mapq or int(row[10]) < min_length:
+            counts["filtered"] += 1
+            continue
+        intervals[row[5]].append((int(row[7]), int(row[8]), 1))
+        reads[row[5]].add(row[0])
+    return intervals, reads, counts
+
+
+def interval_means(intervals, length, size):
+    """Calculate length-weighted mean coverage in each window from intervals."""
+    starts = window_starts(length, size)
+    sums = [0] * len(starts)
+    for start, end, value in intervals:
+        end = min(end, length)
+        while start < end:
+            index = start // size
+            window_end = min((index + 1) * size, end)
+            sums[index] += value * (window_end - start)
+            start = window_end
+    return window_fractions(sums, length, size)
+
+
+def cov_fields(field_id, covs, file_name, cov_range):
+    """Create a base coverage Variable."""
+    fields = {
+        "cov_id": field_id,
+        "cov_range": [
+            min(covs + [cov_range[0]]),
+            max(covs + [cov_range[1]]),
+        ],
+    }
+    fields["cov"] = Variable(
+        field_id,
+        values=covs,
+        meta={"field_id": field_id, "file": file_name},
+        parents=[
+            "children",
+            {
+                "id": "base_coverage",
+                "clamp": 0.01 if fields["cov_range"][0] == 0 else False,
+                "range": fields["cov_range"],
+            },
+            "children",
+        ],
+    )
+    return fields
+
+
 def parse_bam(bam_file, **kwargs):
     """Parse coverage into a Variables."""
     identifiers = kwargs["dependencies"]["identifiers"]
     ids = identifiers.values
-    lengths = kwargs["dependencies"]["length"].values
-    ncounts = kwargs["dependencies"]["ncount"].values
     parts = bam_file.split("=")
     base_name = parts[1]
-    f_char = Path(parts[0]).suffix[1]
     index_file = Path(f"{parts[0]}.csi")
     if index_file.is_file():
         index_file = False
     print(f"Loading mapping data from {parts[0]} as {parts[1]}")
-    _covs = get_coverage(parts[0])
-    if index_file:
+    settings = filter_settings(**kwargs)
+    if settings["min_mapq"] or settings["include_flags"] or settings["exclude_flags"]:
+        if kwargs.get("--cov-gc-correct"):
+            print(
+                "ERROR: Alignment filters are not applied to windowed coverage so "
+                "cannot be combined with --cov-gc-correct"
+            )
+            sys.exit(1)
+        aligned = get_filtered_coverage(parts[0], settings)
+        lengths = dict(zip(ids, kwargs["dependencies"]["length"].values))
+        _covs = {
+            seq_id: bases / lengths[seq_id] if lengths.get(seq_id) else 0
+            for seq_id, bases in aligned.items()
+        }
+    else:
+        _covs = get_coverage(parts[0])
+    if index_file and index_file.is_file():
         os.remove(index_file)
     if not identifiers.validate_list(list(_covs.keys())):
         raise UserWarning(
             "Contig names in the coverage file did not match dataset identifiers."
         )
     covs = [float("%.4f" % (_covs[seq_id]) if seq_id in _covs else 0) for seq_id in ids]
-    field_id = f"{base_name}_cov"
+    fields = cov_fields(f"{base_name}_cov", covs, bam_file, kwargs["cov_range"])
+    fields["cov"].meta.update(settings)
+    return fields
+
+
+def read_cov_fields(field_id, read_covs, file_name, read_cov_range):
+    """Create a read coverage Variable."""
     fields = {
-        "cov_id": field_id,
-        "cov_range": [
-            min(covs + [kwargs["cov_range"][0]]),
-            max(covs + [kwargs["cov_range"][1]]),
+        "read_cov_id": field_id,
+        "read_cov_range": [
+            min(read_covs + [read_cov_range[0]]),
+            max(read_covs + [read_cov_range[1]]),
         ],
     }
-    fields["cov"] = Variable(
+    fields["read_cov"] = Variable(
         field_id,
-        values=covs,
-        meta={"field_id": field_id, "file": bam_file},
+        values=read_covs,
+        meta={"field_id": field_id, "file": file_name},
         parents=[
             "children",
             {
-                "id": "base_coverage",
-                "clamp": 0.01 if fields["cov_range"][0] == 0 else False,
-                "range": fields["cov_range"],
+                "id": "read_coverage",
+                "datatype": "integer",
+                "clamp": 1 if fields["read_cov_range"][0] == 0 else False,
+                "range": fields["read_cov_range"],
             },
             "children",
         ],
@@ -71,6 +279,212 @@ def parse_bam(bam_file, **kwargs):
     return fields
 
 
+def parse_paf(paf_file, **kwargs):
+    """Parse base and read coverage from PAF alignments into Variables."""
+    identifiers = kwargs["dependencies"]["identifiers"]
+    lengths = kwargs["dependencies"]["length"].values
+    parts = paf_file.split("=")
+    base_name = parts[1]
+    min_mapq = int(kwargs.get("--cov-min-mapq") or 0)
+    min_length = int(kwargs.get("--cov-min-length") or 0)
+    print(f"Loading alignment data from {parts[0]} as {parts[1]}")
+    intervals, reads, counts = parse_paf_file(parts[0], min_mapq, min_length)
+    if not identifiers.validate_list(list(intervals.keys())):
+        raise UserWarning(
+            "Contig names in the coverage file did not match dataset identifiers."
+        )
+    covs = []
+    read_covs = []
+    for seq_id, length in zip(identifiers.values, lengths):
+        aligned = sum(end - start for start, end, _ in intervals.get(seq_id, []))
+        covs.append(float("%.4f" % (aligned / length)) if length else 0)
+        read_covs.append(len(reads.get(seq_id, [])))
+    field_id = f"{base_name}_cov"
+    fields = cov_fields(field_id, covs, paf_file, kwargs["cov_range"])
+    fields.update(
+        read_cov_fields(
+            f"{base_name}_read_cov", read_covs, paf_file, kwargs["read_cov_range"]
+        )
+    )
+    for key in ("cov", "read_cov"):
+        fields[key].meta.update(
+            {
+                "min_mapq": min_mapq,
+                "min_length": min_length,
+                "alignments": counts["alignments"],
+                "filtered": counts["filtered"],
+            }
+        )
+    fields["windows"] = interval_windows(
+        field_id, f"{base_name} coverage", intervals, identifiers, lengths, kwargs
+    )
+    return fields
+
+
+def interval_windows(field_id, name, intervals, identifiers, lengths, kwargs):
+    """Create windowed mean coverage fields from intervals."""
+    windows = []
+    if not intervals:
+        return windows
+    for window in list_windows(kwargs["meta"]):
+        windows.append(
+            windows_field(
+                field_id,
+                name,
+                window,
+                [
+                    interval_means(
+                        intervals.get(seq_id, []),
+                        length,
+                        window_size(length, window["value"]),
+                    )
+                    for seq_id, length in zip(identifiers.values, lengths)
+                ],
+                ["children"],
+            )
+        )
+    return windows
+
+
+def parse_depth(depth_file, **kwargs):
+    """Parse mosdepth, bedGraph or bigWig coverage into Variables."""
+    identifiers = kwargs["dependencies"]["identifiers"]
+    lengths = kwargs["dependencies"]["length"].values
+    parts = depth_file.split("=")
+    base_name = parts[1]
+    _suffix, file_format = depth_format(parts[0])
+    print(f"Loading depth data from {parts[0]} as {parts[1]}")
+    intervals = {}
+    if file_format == "summary":
+        _covs = parse_mosdepth_summary(parts[0])
+    else:
+        if file_format == "bigwig":
+            intervals = parse_bigwig(parts[0])
+        else:
+            intervals = parse_depth_intervals(parts[0])
+        _covs = {
+            seq_id: sum((end - start) * value for start, end, value in seq_intervals)
+            for seq_id, seq_intervals in intervals.items()
+        }
+    if not identifiers.validate_list(list(_covs.keys())):
+        raise UserWarning(
+            "Contig names in the coverage file did not match dataset identifiers."
+        )
+    covs = []
+    for seq_id, length in zip(identifiers.values, lengths):
+        value = _covs.get(seq_id, 0)
+        if intervals:
+            value = value / length if length else 0
+        covs.append(float("%.4f" % value))
+    field_id = f"{base_name}_cov"
+    fields = cov_fields(field_id, covs, depth_file, kwargs["cov_range"])
+    fields["windows"] = interval_windows(
+        field_id, f"{base_name} coverage", intervals, identifiers, lengths, kwargs
+    )
+    return fields
+
+
+def fit_gc_model(pairs, bin_width=0.01, min_windows=10):
+    """
+    Fit expected window coverage as a function of GC.
+
+    Windows are binned by GC and the median coverage in each bin is averaged
+    with neighbouring bins, weighted by window count. Bins with too few windows
+    borrow from the nearest well-populated bins.
+    """
+    binned = defaultdict(list)
+    for gc, cov in pairs:
+        binned[round(gc / bin_width)].append(cov)
+    medians = {
+        gc_bin: (median(covs), len(covs))
+        for gc_bin, covs in binned.items()
+        if len(covs) >= min_windows
+    }
+    if not medians:
+        return None
+    model = {}
+    for gc_bin in binned:
+        near = [i for i in range(gc_bin - 2, gc_bin + 3) if i in medians]
+        if not near:
+            near = [min(medians, key=lambda i: abs(i - gc_bin))]
+        total = sum(medians[i][1] for i in near)
+        model[gc_bin] = sum(medians[i][0] * medians[i][1] for i in near) / total
+    return model
+
+
+def gc_corrected_fields(cov_field, windows, **kwargs):
+    """Create a GC-bias corrected coverage Variable from windowed GC and coverage."""
+    meta = kwargs["meta"]
+    field_id = cov_field.field_id
+    window_id = f"{field_id}_windows"
+    cov_windows = next(
+        (field for field in windows if field.field_id == window_id), None
+    )
+    if cov_windows is None:
+        cov_windows = fetch_field(kwargs["DIRECTORY"], window_id, meta)
+    gc_windows = fetch_field(kwargs["DIRECTORY"], "gc_windows", meta)
+    if not cov_windows or not gc_windows:
+        print(f"WARN: Unable to GC correct {field_id} without gc_windows/{window_id}")
+        return []
+    lengths = kwargs["dependencies"]["length"].values
+    windows = []
+    pairs = []
+    for seq_gcs, seq_covs, length in zip(
+        gc_windows.values, cov_windows.values, lengths
+    ):
+        if not seq_covs or len(seq_gcs) != len(seq_covs):
+            windows.append(None)
+            continue
+        size = window_size(length, 0.1)
+        spans = window_spans(length, size)
+        if len(spans) != len(seq_covs):
+            spans = [1] * len(seq_covs)
+        seq_windows = [
+            (gc[0], cov[0], span)
+            for gc, cov, span in zip(seq_gcs, seq_covs, spans)
+            if gc[0] is not None and cov[0] is not None
+        ]
+        windows.append(seq_windows or None)
+        pairs += [(gc, cov) for gc, cov, _ in seq_windows]
+    model = fit_gc_model(pairs)
+    if model is None:
+        print(f"WARN: Too few windows to fit a GC model for {field_id}")
+        return []
+    overall = median([cov for _, cov in pairs])
+    covs = []
+    for seq_windows, raw_cov in zip(windows, cov_field.values):
+        if seq_windows is None:
+            covs.append(raw_cov)
+            continue
+        total = 0
+        for gc, cov, span in seq_windows:
+            expected = model[round(gc / 0.01)]
+            total += (cov * overall / expected if expected else cov) * span
+        covs.append(float("%.4f" % (total / sum(span for _, _, span in seq_windows))))
+    corrected_id = f"{field_id[:-4]}_gc_corrected_cov"
+    return [
+        Variable(
+            corrected_id,
+            values=covs,
+            meta={
+                "field_id": corrected_id,
+                "name": corrected_id,
+                "scale": "scaleLog",
+                "datatype": "float",
+                "range": [min(covs), max(covs)],
+                "clamp": 0.01 if min(covs) == 0 else False,
+                "gc_model": [
+                    [float("%.2f" % (gc_bin * 0.01)), float("%.4f" % expected)]
+                    for gc_bin, expected in sorted(model.items())
+                ],
+                "preload": False,
+                "active": False,
+            },
+            parents=["children"],
+        )
+    ]
+
+
 def apply_filter(ids, fastq_files, **kwargs):
     """Filter FASTQ file based on read alignment file."""
     suffix = kwargs["--suffix"]
@@ -150,7 +564,11 @@ def base_names(files):
             name = parts[1]
             names[name] = parts[0]
         else:
-            name = Path(file).stem
+            suffix, _file_format = depth_format(file)
+            if suffix is not None:
+                name = Path(file).name[: -len(suffix)]
+            else:
+                name = Path(file).stem
             if name in names:
                 unique = False
             else:
@@ -178,6 +596,14 @@ def parse(files, **kwargs):
             fields = parse_json_cov(
                 file, **kwargs, cov_range=cov_range, read_cov_range=read_cov_range
             )
+        elif depth_format(file.split("=")[0])[1] == "paf":
+            fields = parse_paf(
+                file, **kwargs, cov_range=cov_range, read_cov_range=read_cov_range
+            )
+        elif depth_format(file.split("=")[0])[1] is not None:
+            fields = parse_depth(
+                file, **kwargs, cov_range=cov_range, read_cov_range=read_cov_range
+            )
         else:
             fields = parse_bam(
                 file, **kwargs, cov_range=cov_range, read_cov_range=read_cov_range
@@ -190,9 +616,39 @@ def parse(files, **kwargs):
         if "read_cov" in fields:
             parsed.append(fields["read_cov"])
             read_cov_range = fields["read_cov_range"]
+        parsed += fields.get("windows", [])
+        if kwargs.get("--cov-gc-correct") and "cov" in fields:
+            parsed += gc_corrected_fields(
+                fields["cov"], fields.get("windows", []), **kwargs
+            )
+    if kwargs.get("--cov-profile"):
+        parsed += coverage_profile(parsed, **kwargs)
     return parsed
 
 
+def coverage_profile(parsed, **kwargs):
+    """Compare base coverage across all libraries in the dataset."""
+    meta = kwargs["meta"]
+    cov_fields = {
+        field.field_id: field
+        for field in parsed
+        if field.field_id.endswith("_cov")
+        and not field.field_id.endswith(("_read_cov", "_gc_corrected_cov"))
+    }
+    if meta.has_field("base_coverage"):
+        for child in meta.field_meta("base_coverage").get("children", []):
+            if child["id"] not in cov_fields and not child["id"].endswith(
+                "_gc_corrected_cov"
+            ):
+                field = fetch_field(kwargs["DIRECTORY"], child["id"], meta)
+                if field:
+                    cov_fields[child["id"]] = field
+    if len(cov_fields) < 2:
+        print("WARN: At least two coverage libraries are needed for a profile")
+        return []
+    return profile_fields(list(cov_fields.values()))
+
+
 def parent():
     """Set standard metadata for Coverage."""
     coverage = {
@@ -0,0 +1,102 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""Compare coverage across libraries for differential coverage binning."""
+
+import math
+from itertools import combinations
+
+from .field import Array
+from .field import Variable
+from .tetra import principal_components
+from .tetra import project
+
+PARENTS = [
+    "children",
+    {
+        "id": "differential_coverage",
+        "name": "Differential coverage",
+        "datatype": "float",
+        "type": "variable",
+        "scale": "scaleLinear",
+    },
+    "children",
+]
+
+
+def clr(profile, pseudocount=0.01):
+    """Centred log-ratio transform a coverage profile."""
+    logs = [math.log(value + pseudocount) for value in profile]
+    mean = sum(logs) / len(logs)
+    return [value - mean for value in logs]
+
+
+def variable(field_id, name, values, **meta):
+    """Create a coverage profile Variable."""
+    return Variable(
+        field_id,
+        values=values,
+        meta={
+            "field_id": field_id,
+            "name": name,
+            "scale": "scaleLinear",
+            "datatype": "float",
+            "range": [min(values), max(values)],
+            "preload": False,
+            "active": False,
+            **meta,
+        },
+        parents=PARENTS,
+    )
+
+
+def profile_fields(cov_fields):
+    """Create coverage profile, pairwise log-ratio and embedding Fields."""
+    cov_fields = sorted(cov_fields, key=lambda field: field.field_id)
+    libraries = [field.field_id[: -len("_cov")] for field in cov_fields]
+    profiles = [list(values) for values in zip(*[f.values for f in cov_fields])]
+    fields = [
+        Array(
+            "coverage_profile",
+            values=profiles,
+            meta={
+                "field_id": "coverage_profile",
+                "name": "Coverage profile",
+                "type": "array",
+                "datatype": "float",
+                "preload": False,
+                "active": False,
+            },
+            headers=libraries,
+            parents=PARENTS,
+        )
+    ]
+    for (i, first), (j, second) in combinations(enumerate(libraries), 2):
+        field_id = f"{first}_{second}_log_ratio"
+        values = [
+            float("%.4f" % math.log2((profile[i] + 0.01) / (profile[j] + 0.01)))
+            for profile in profiles
+        ]
+        fields.append(
+            variable(
+                field_id,
+                f"{first}/{second} coverage log2 ratio",
+                values,
+                libraries=[first, second],
+            )
+        )
+    transformed = [clr(profile) for profile in profiles]
+    count = min(2, len(libraries) - 1)
+    means, components = principal_components(transformed, count)
+    for index, component in enumerate(components):
+        field_id = "coverage_profile_pc%d" % (index + 1)
+        fields.append(
+            variable(
+                field_id,
+                "Coverage profile PC%d" % (index + 1),
+                [project(profile, means, component) for profile in transformed],
+                libraries=libraries,
+            )
+        )
+    return fields
@@ -3,20 +3,35 @@
 
 """Parse FASTA sequence into Fields."""
 
+import re
 from collections import Counter
 from collections import OrderedDict
+from copy import deepcopy
 
 from blobtk import filter
 from tqdm import tqdm
 
 from ..lib import file_io
+from .bed import field_settings
 from .field import Identifier
 from .field import Variable
+from .telomere import telomere_fields
+from .tetra import tetra_fields
+from .tetra import tetranucleotide_frequencies
+
+
+COMPOSITION_FIELDS = ["masked", "gaps", "gapspan", "iupac"]
 
 
 def base_composition(seq_str):
     """Sequence base composition summary."""
-    counts = Counter(seq_str.upper())
+    raw_counts = Counter(seq_str)
+    counts = Counter()
+    masked_count = 0
+    for base, count in raw_counts.items():
+        counts[base.upper()] += count
+        if base.islower():
+            masked_count += count
     at_bases = ["A", "T", "W"]
     gc_bases = ["C", "G", "S"]
     at_count = 0
@@ -28,7 +43,16 @@ def base_composition(seq_str):
     acgt_count = at_count + gc_count
     gc_portion = float("%.4f" % (gc_count / acgt_count))
     n_count = len(seq_str) - acgt_count
-    return gc_portion, n_count
+    gaps = [len(match) for match in re.findall(r"[Nn]+", seq_str)]
+    iupac_count = len(seq_str) - sum(counts[base] for base in "ACGTN")
+    return {
+        "gc": gc_portion,
+        "ncount": n_count,
+        "masked": float("%.4f" % (masked_count / len(seq_str))) if seq_str else 0,
+        "gaps": len(gaps),
+        "gapspan": sum(gaps),
+        "iupac": iupac_count,
+    }
 
 
 def apply_filter(ids, fasta_file, **kwargs):
@@ -43,15 +67,24 @@ def parse(file, **kwargs):
     _lengths = OrderedDict()
     _gc_portions = OrderedDict()
     _n_counts = OrderedDict()
+    _composition = {key: OrderedDict() for key in COMPOSITION_FIELDS}
+    _tetra = OrderedDict()
     lengths = []
     gc_portions = []
     n_counts = []
+    composition = {key: [] for key in COMPOSITION_FIELDS}
     print(f"Loading sequences from {file}")
     pbar = tqdm(file_io.stream_fasta(file))
     for seq_id, seq_str in pbar:
         pbar.set_description(f" - processing {seq_id}")
         _lengths[seq_id] = len(seq_str)
-        _gc_portions[seq_id], _n_counts[seq_id] = base_composition(seq_str)
+        seq_composition = base_composition(seq_str)
+        _gc_portions[seq_id] = seq_composition["gc"]
+        _n_counts[seq_id] = seq_composition["ncount"]
+        for key in COMPOSITION_FIELDS:
+            _composition[key][seq_id] = seq_composition[key]
+        if kwargs.get("--tetra"):
+            _tetra[seq_id] = tetranucleotide_frequencies(seq_str)
     identifiers = kwargs["dependencies"]["identifiers"]
     if not identifiers:
         identifiers = Identifier(
@@ -66,6 +99,8 @@ def parse(file, **kwargs):
         lengths.append(_lengths[seq_id] if seq_id in _lengths else 0)
         gc_portions.append(_gc_portions[seq_id] if seq_id in _gc_portions else 0)
         n_counts.append(_n_counts[seq_id] if seq_id in _n_counts else 0)
+        for key in COMPOSITION_FIELDS:
+            composition[key].append(_composition[key].get(seq_id, 0))
     kwargs["meta"].assembly.update({"span": sum(lengths)})
     parsed.append(
         Variable(
@@ -113,6 +148,27 @@ def parse(file, **kwargs):
             parents=[],
         )
     )
+    settings = field_settings()
+    for key in COMPOSITION_FIELDS:
+        meta = deepcopy(settings[key]["meta"])
+        meta.update(
+            {
+                "field_id": key,
+                "range": [min(composition[key]), max(composition[key])],
+            }
+        )
+        parsed.append(Variable(key, meta=meta, values=composition[key], parents=[]))
+    if kwargs.get("--telomere"):
+        parsed += telomere_fields(file, identifiers, **kwargs)
+    if kwargs.get("--tetra"):
+        parsed += tetra_fields(
+            [
+                _tetra.get(seq_id, tetranucleotide_frequencies(""))
+                for seq_id in identifiers.values
+            ],
+            lengths,
+            **kwargs,
+        )
     if "x" not in kwargs["meta"].plot:
         kwargs["meta"].plot.update({"x": "gc"})
     if "z" not in kwargs["meta"].plot:
@@ -151,5 +207,12 @@ def remove_from_meta(meta):
         meta.plot.pop("z", None)
     if meta.has_field("ncount"):
         field_ids += meta.remove_field("ncount")
+    for key in COMPOSITION_FIELDS:
+        if meta.has_field(key):
+            field_ids += meta.remove_field(key)
+    if meta.has_field("telomere"):
+        field_ids += meta.remove_field("telomere")
+    if meta.has_field("tetranucleotide"):
+        field_ids += meta.remove_field("tetranucleotide")
     meta.assembly.pop("file", None)
     return field_ids
@@ -0,0 +1,190 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""Parse NCBI FCS-GX contamination reports into Fields."""
+
+from collections import defaultdict
+
+from ..lib import file_io
+from .bed import fraction
+from .bed import intervals_to_windows
+from .bed import list_windows
+from .bed import merge_intervals
+from .bed import window_fractions
+from .bed import window_size
+from .bed import windows_field
+from .field import Category
+from .field import MultiArray
+from .field import Variable
+
+ACTIONS = ["EXCLUDE", "TRIM", "FIX", "REVIEW", "REVIEW_RARE", "INFO"]
+
+
+def action_rank(action):
+    """Rank actions by severity, unknown actions are least severe."""
+    try:
+        return ACTIONS.index(action)
+    except ValueError:
+        return len(ACTIONS)
+
+
+def parse_report(report_file):
+    """Parse flagged ranges from an fcs_gx_report.txt file."""
+    results = defaultdict(list)
+    for line in file_io.stream_file(report_file):
+        if line.startswith("#"):
+            continue
+        row = line.rstrip("\n").split("\t")
+        if len(row) < 7:
+            continue
+        try:
+            start, end = int(row[1]), int(row[2])
+        except ValueError:
+            continue
+        try:
+            coverage = float(row[6])
+        except ValueError:
+            coverage = 0
+        results[row[0]].append(
+            {
+                "start": start,
+                "end": end,
+                "action": row[4],
+                "division": row[5],
+                "coverage": coverage,
+            }
+        )
+    return results
+
+
+def parse_fcs_gx(report_file, identifiers, lengths, windows):
+    """Parse an FCS-GX report into Category, Variable and MultiArray Fields."""
+    report_file, *prefix = report_file.split("=")
+    prefix = prefix[0] if prefix else "fcs_gx"
+    results = parse_report(report_file)
+    if results and not identifiers.validate_list(list(results.keys())):
+        raise UserWarning(
+            "Contig names in the FCS-GX report did not match dataset identifiers."
+        )
+    actions = []
+    divisions = []
+    coverages = []
+    trims = []
+    ranges = []
+    window_values = defaultdict(list)
+    for seq_id, length in zip(identifiers.values, lengths):
+        rows = sorted(
+            results.get(seq_id, []),
+            key=lambda row: (action_rank(row["action"]), -row["coverage"]),
+        )
+        actions.append(rows[0]["action"] if rows else "none")
+        divisions.append(rows[0]["division"] if rows else "none")
+        coverages.append(max([row["coverage"] for row in rows] + [0]))
+        trimmed = [
+            [row["start"], row["end"]] for row in rows if row["action"] == "TRIM"
+        ]
+        ranges.append(
+            [
+                [row["start"], row["end"], row["action"], row["division"]]
+                for row in sorted(rows, key=lambda row: row["start"])
+            ]
+        )
+        trimmed = merge_intervals(trimmed)
+        trims.append(fraction(sum(end - start + 1 for start, end in trimmed), length))
+        for window in windows:
+            size = window_size(length, window["value"])
+            covered = intervals_to_windows(trimmed, length, size)
+            window_values[window["title"]].append(
+                window_fractions(covered, length, size)
+            )
+    meta = {"preload": False, "active": False, "file": report_file}
+    fields = [
+        Category(
+            "%s_action" % prefix,
+            values=actions,
+            meta={**meta, "field_id": "%s_action" % prefix, "name": "FCS-GX action"},
+            parents=["children"],
+        ),
+        Category(
+            "%s_division" % prefix,
+            values=divisions,
+            meta={
+                **meta,
+                "field_id": "%s_division" % prefix,
+                "name": "FCS-GX division",
+            },
+            parents=["children"],
+        ),
+        Variable(
+            "%s_coverage" % prefix,
+            values=coverages,
+            meta={
+                **meta,
+                "field_id": "%s_coverage" % prefix,
+                "name": "FCS-GX contaminant coverage",
+                "scale": "scaleLinear",
+                "datatype": "float",
+                "range": [0, 100],
+            },
+            parents=["children"],
+        ),
+        Variable(
+            "%s_trim" % prefix,
+            values=trims,
+            meta={
+                **meta,
+                "field_id": "%s_trim" % prefix,
+                "name": "FCS-GX trimmed fraction",
+                "scale": "scaleLinear",
+                "datatype": "float",
+                "range": [0, 1],
+            },
+            parents=["children"],
+        ),
+        MultiArray(
+            "%s_ranges" % prefix,
+            values=ranges,
+            meta={
+                **meta,
+                "field_id": "%s_ranges" % prefix,
+                "name": "FCS-GX flagged ranges",
+                "type": "multiarray",
+                "datatype": "mixed",
+            },
+            headers=["start", "end", "action", "division"],
+            parents=["children"],
+            category_slot=3,
+        ),
+    ]
+    for window in windows:
+        fields.append(
+            windows_field(
+                "%s_trim" % prefix,
+                "FCS-GX trimmed fraction",
+                window,
+                window_values[window["title"]],
+                ["children"],
+            )
+        )
+    return fields
+
+
+def parse(files, **kwargs):
+    """Parse all FCS-GX reports."""
+    parsed = []
+    windows = list_windows(kwargs["meta"])
+    for file in files:
+        parsed += parse_fcs_gx(
+            file,
+            identifiers=kwargs["dependencies"]["identifiers"],
+            lengths=kwargs["dependencies"]["length"].values,
+            windows=windows,
+        )
+    return parsed
+
+
+def parent():
+    """Set standard metadata for FCS-GX."""
+    fcs_gx = {"datatype": "mixed", "type": "array", "id": "fcs_gx", "name": "FCS-GX"}
+    return [fcs_gx]
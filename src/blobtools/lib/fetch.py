@@ -92,7 +92,18 @@ def fetch_metadata(path_to_dataset, **kwargs):
 
 def fetch_taxdump(path_to_taxdump):
     """Load Taxdump from file."""
-    json_file = "%s/taxdump.json" % path_to_taxdump
+    if Path(Taxdump.index_file(path_to_taxdump)).exists():
+        if Taxdump.index_is_current(path_to_taxdump):
+            print("Loading indexed taxdump")
+            return Taxdump(path_to_taxdump)
+        print("WARN: Taxdump index is out of date, rebuilding from taxdump files")
+        taxdump = Taxdump(path_to_taxdump)
+        taxdump.write_index()
+        return taxdump
+    if os.path.isfile(path_to_taxdump):
+        json_file = "%s.taxdump.json" % path_to_taxdump
+    else:
+        json_file = "%s/taxdump.json" % path_to_taxdump
     if not Path(json_file).exists():
         print("Parsing taxdump")
     else:
@@ -103,6 +114,11 @@ def fetch_taxdump(path_to_taxdump):
         file_io.write_file(json_file, taxdump.values_to_dict())
     else:
         taxdump = Taxdump(path_to_taxdump, **data)
+        if "merged" not in data and os.path.isdir(path_to_taxdump):
+            # caches written before merged taxids were stored
+            print("Adding merged and deleted taxids to parsed taxdump")
+            taxdump.load_merged()
+            file_io.write_file(json_file, taxdump.values_to_dict())
     return taxdump
 
 
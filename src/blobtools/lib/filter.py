@@ -38,7 +38,8 @@ Options:
     --table-fields STRING     Comma separated list of field IDs to include in the
                               table output. Use 'plot' to include all plot axes.
                               [Default: plot]
-    --taxdump DIRECTORY       Location of NCBI new_taxdump directory.
+    --taxdump DIRECTORY       Location of NCBI new_taxdump directory or GTDB/custom
+                              lineage file.
     --taxrule STRING          Taxrule used when processing hits.
 """
 
@@ -402,7 +403,9 @@ def main(args):
             if section["title"] == "readMapping":
                 libraries = []
                 for field in meta.list_fields():
-                    if field.endswith("_cov") and not field.endswith("_read_cov"):
+                    if field.endswith("_cov") and not field.endswith(
+                        ("_read_cov", "_gc_corrected_cov")
+                    ):
                         library = field.replace("_cov", "")
                         libraries.append(library)
                         fields.update(
@@ -0,0 +1,161 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""Parse GFA assembly graph links into Fields."""
+
+from collections import defaultdict
+
+from ..lib import file_io
+from .fetch import fetch_field
+from .field import Category
+from .field import MultiArray
+from .field import Variable
+
+
+def parse_gfa_file(gfa_file):
+    """Parse segment names and links from a GFA1 file."""
+    segments = []
+    links = defaultdict(set)
+    for line in file_io.stream_file(gfa_file):
+        row = line.rstrip("\n").split("\t")
+        if row[0] == "S" and len(row) > 1:
+            segments.append(row[1])
+        elif row[0] == "L" and len(row) > 4:
+            if row[1] != row[3]:
+                links[row[1]].add(row[3])
+                links[row[3]].add(row[1])
+    return segments, links
+
+
+def synonym_lookup(identifiers, meta, directory):
+    """Map identifiers and any stored synonyms to identifiers."""
+    lookup = {seq_id: seq_id for seq_id in identifiers.values}
+    synonyms = meta.field_meta("synonyms") if meta.has_field("synonyms") else {}
+    for child in synonyms.get("children", []):
+        field = fetch_field(directory, child["id"], meta)
+        if not field:
+            continue
+        for seq_id, names in zip(identifiers.values, field.values):
+            for name in names:
+                lookup.setdefault(name, seq_id)
+    return lookup
+
+
+def connected_components(nodes, links):
+    """Find connected components, largest first."""
+    seen = set()
+    components = []
+    for node in nodes:
+        if node in seen:
+            continue
+        seen.add(node)
+        stack = [node]
+        component = []
+        while stack:
+            current = stack.pop()
+            component.append(current)
+            for neighbour in links.get(current, []):
+                if neighbour not in seen:
+                    seen.add(neighbour)
+                    stack.append(neighbour)
+        components.append(component)
+    return sorted(components, key=len, reverse=True)
+
+
+def parse_gfa(gfa_file, identifiers, meta, directory):
+    """Parse a GFA file into component, degree and link Fields."""
+    gfa_file, *prefix = gfa_file.split("=")
+    prefix = prefix[0] if prefix else "gfa"
+    segments, links = parse_gfa_file(gfa_file)
+    lookup = synonym_lookup(identifiers, meta, directory)
+    names = {segment: lookup.get(segment, segment) for segment in segments}
+    if not any(segment in lookup for segment in segments):
+        raise UserWarning(
+            "Segment names in the GFA file did not match dataset identifiers."
+        )
+    graph = defaultdict(set)
+    for segment, neighbours in links.items():
+        for neighbour in neighbours:
+            graph[names.get(segment, segment)].add(names.get(neighbour, neighbour))
+    component_ids = {}
+    index = 0
+    for component in connected_components(list(names.values()), graph):
+        if len(component) == 1:
+            label = "unlinked"
+        else:
+            index += 1
+            label = "component_%d" % index
+        for node in component:
+            component_ids[node] = label
+    components = []
+    degrees = []
+    neighbours = []
+    for seq_id in identifiers.values:
+        components.append(component_ids.get(seq_id, "none"))
+        degrees.append(len(graph.get(seq_id, [])))
+        neighbours.append([[neighbour] for neighbour in sorted(graph.get(seq_id, []))])
+    field_meta = {"preload": False, "active": False, "file": gfa_file}
+    return [
+        Category(
+            "%s_component" % prefix,
+            values=components,
+            meta={
+                **field_meta,
+                "field_id": "%s_component" % prefix,
+                "name": "%s component" % prefix,
+            },
+            parents=["children"],
+        ),
+        Variable(
+            "%s_degree" % prefix,
+            values=degrees,
+            meta={
+                **field_meta,
+                "field_id": "%s_degree" % prefix,
+                "name": "%s degree" % prefix,
+                "scale": "scaleLinear",
+                "datatype": "integer",
+                "range": [min(degrees), max(degrees)],
+            },
+            parents=["children"],
+        ),
+        MultiArray(
+            "%s_links" % prefix,
+            values=neighbours,
+            meta={
+                **field_meta,
+                "field_id": "%s_links" % prefix,
+                "name": "%s linked to" % prefix,
+                "type": "multiarray",
+                "datatype": "string",
+            },
+            headers=["identifier"],
+            parents=["children"],
+            category_slot=0,
+        ),
+    ]
+
+
+def parse(files, **kwargs):
+    """Parse all GFA files."""
+    parsed = []
+    for file in files:
+        parsed += parse_gfa(
+            file,
+            identifiers=kwargs["dependencies"]["identifiers"],
+            meta=kwargs["meta"],
+            directory=kwargs["DIRECTORY"],
+        )
+    return parsed
+
+
+def parent():
+    """Set standard metadata for assembly graphs."""
+    graph = {
+        "datatype": "mixed",
+        "type": "array",
+        "id": "assembly_graph",
+        "name": "Assembly graph",
+    }
+    return [graph]
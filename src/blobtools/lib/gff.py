@@ -0,0 +1,198 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""Parse GFF3/GTF gene annotations into Variable Fields."""
+
+import re
+from collections import defaultdict
+
+from ..lib import file_io
+from .bed import fraction
+from .bed import intervals_to_windows
+from .bed import list_windows
+from .bed import merge_intervals
+from .bed import validate_range
+from .bed import window_fractions
+from .bed import window_size
+from .bed import window_spans
+from .bed import window_starts
+from .bed import windows_field
+from .field import Variable
+
+FIELDS = {
+    "gene_count": {"name": "Gene count", "datatype": "integer"},
+    "coding_fraction": {"name": "Coding fraction", "datatype": "float"},
+    "mean_gene_length": {"name": "Mean gene length", "datatype": "float"},
+    "gene_density": {"name": "Genes per Mb", "datatype": "float"},
+}
+
+
+def parse_attributes(string):
+    """Parse GFF3 (key=value) or GTF (key "value") attributes."""
+    attributes = {}
+    for part in string.strip().rstrip(";").split(";"):
+        part = part.strip()
+        if not part:
+            continue
+        if "=" in part:
+            key, value = part.split("=", 1)
+        else:
+            try:
+                key, value = re.split(r"\s+", part, 1)
+            except ValueError:
+                continue
+        attributes[key] = value.strip('"')
+    return attributes
+
+
+def parse_gff_file(gff_file):
+    """Parse gene and CDS intervals from a GFF3/GTF file."""
+    genes = defaultdict(dict)
+    spans = defaultdict(dict)
+    cds = defaultdict(list)
+    for line in file_io.stream_file(gff_file):
+        if line.startswith("##FASTA"):
+            break
+        if line.startswith("#"):
+            continue
+        row = line.rstrip("\n").split("\t")
+        if len(row) < 9:
+            continue
+        seq_id, feature = row[0], row[2]
+        try:
+            start, end = int(row[3]), int(row[4])
+        except ValueError:
+            continue
+        if feature == "CDS":
+            cds[seq_id].append((start, end))
+        attributes = parse_attributes(row[8])
+        gtf = "=" not in row[8].split(";")[0]
+        if feature == "gene":
+            gene_id = attributes.get(
+                "ID", attributes.get("gene_id", len(genes[seq_id]))
+            )
+            genes[seq_id][gene_id] = [start, end]
+        elif gtf and "gene_id" in attributes:
+            # GTF files need not include gene features so use transcript spans
+            span = spans[seq_id].setdefault(attributes["gene_id"], [start, end])
+            span[0] = min(span[0], start)
+            span[1] = max(span[1], end)
+    for seq_id, seq_spans in spans.items():
+        for gene_id, span in seq_spans.items():
+            if gene_id not in genes[seq_id]:
+                genes[seq_id][gene_id] = span
+    return (
+        {seq_id: list(seq_genes.values()) for seq_id, seq_genes in genes.items()},
+        {seq_id: merge_intervals(intervals) for seq_id, intervals in cds.items()},
+    )
+
+
+def gene_stats(genes, cds, length):
+    """Calculate gene statistics for a single sequence."""
+    count = len(genes)
+    coding = sum(end - start + 1 for start, end in cds)
+    mean_length = sum(end - start + 1 for start, end in genes) / count if count else 0
+    return {
+        "gene_count": count,
+        "coding_fraction": fraction(coding, length),
+        "mean_gene_length": float("%.1f" % mean_length),
+        "gene_density": float("%.3g" % (count / length * 1000000)) if length else 0,
+    }
+
+
+def gene_window_stats(genes, cds, length, size):
+    """Calculate gene statistics for each window along a sequence."""
+    starts = window_starts(length, size)
+    counts = [0] * len(starts)
+    for start, end in genes:
+        index = min(((start + end) // 2 - 1) // size, len(starts) - 1)
+        counts[max(index, 0)] += 1
+    coding = intervals_to_windows(cds, length, size)
+    return {
+        "gene_count": counts,
+        "coding_fraction": window_fractions(coding, length, size),
+        "gene_density": [
+            float("%.3g" % (count / span * 1000000)) if span else 0
+            for count, span in zip(counts, window_spans(length, size))
+        ],
+    }
+
+
+def parse_gff(gff_file, identifiers, lengths, windows):
+    """Parse a GFF3/GTF file into Variable and windowed MultiArray Fields."""
+    gff_file, *prefix = gff_file.split("=")
+    prefix = prefix[0] if prefix else "gff"
+    genes, cds = parse_gff_file(gff_file)
+    if not identifiers.validate_list(list(set(genes.keys()) | set(cds.keys()))):
+        raise UserWarning(
+            "Contig names in the GFF file did not match dataset identifiers."
+        )
+    values = defaultdict(list)
+    window_values = defaultdict(lambda: defaultdict(list))
+    for seq_id, length in zip(identifiers.values, lengths):
+        stats = gene_stats(genes.get(seq_id, []), cds.get(seq_id, []), length)
+        for key, value in stats.items():
+            values[key].append(value)
+        for window in windows:
+            size = window_size(length, window["value"])
+            stats = gene_window_stats(
+                genes.get(seq_id, []), cds.get(seq_id, []), length, size
+            )
+            for key, value in stats.items():
+                window_values[window["title"]][key].append(value)
+    fields = []
+    for key, settings in FIELDS.items():
+        field_id = "%s_%s" % (prefix, key)
+        meta = {
+            "field_id": field_id,
+            "name": "%s %s" % (prefix, settings["name"]),
+            "scale": "scaleLinear",
+            "datatype": settings["datatype"],
+            "range": [min(values[key]), max(values[key])],
+            "preload": False,
+            "active": False,
+            "file": gff_file,
+        }
+        validate_range(meta)
+        fields.append(
+            Variable(field_id, meta=meta, values=values[key], parents=["children"])
+        )
+        for window in windows:
+            if key in window_values[window["title"]]:
+                fields.append(
+                    windows_field(
+                        field_id,
+                        meta["name"],
+                        window,
+                        window_values[window["title"]][key],
+                        ["children"],
+                    )
+                )
+    return fields
+
+
+def parse(files, **kwargs):
+    """Parse all GFF3/GTF files."""
+    parsed = []
+    windows = list_windows(kwargs["meta"])
+    for file in files:
+        parsed += parse_gff(
+            file,
+            identifiers=kwargs["dependencies"]["identifiers"],
+            lengths=kwargs["dependencies"]["length"].values,
+            windows=windows,
+        )
+    return parsed
+
+
+def parent():
+    """Set standard metadata for gene annotations."""
+    annotation = {
+        "datatype": "float",
+        "type": "variable",
+        "scale": "scaleLinear",
+        "id": "gene_annotation",
+        "name": "Gene annotation",
+    }
+    return [annotation]
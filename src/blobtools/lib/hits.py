@@ -6,6 +6,7 @@
 """Parse BLAST results into MultiArray Field."""
 
 import math
+import os
 import re
 from collections import Counter
 from collections import defaultdict
@@ -13,15 +14,33 @@ from itertools import groupby
 from operator import itemgetter
 
 from ..lib import file_io
+from .bed import window_setting
+from .bed import window_size
 from .field import Category
 from .field import MultiArray
 from .field import Variable
 
 
-def parse_blast(blast_file, cols, results=None, index=0, evalue=1, bitscore=1):
-    """Parse file into dict of lists."""
+def parse_blast(
+    blast_file,
+    cols,
+    results=None,
+    index=0,
+    evalue=1,
+    bitscore=1,
+    accessions=None,
+    lineages=False,
+):
+    """
+    Parse file into dict of lists.
+
+    Taxids from a lineage file taxdump are synthetic, so when lineages is set
+    hits are always matched to a taxid through their accession.
+    """
     if results is None:
         results = defaultdict(list)
+    if accessions is None:
+        accessions = {}
     bitscores = {}
     blastp = {}
 
@@ -86,10 +105,14 @@ def parse_blast(blast_file, cols, results=None, index=0, evalue=1, bitscore=1):
             except ValueError:
                 # no taxid for this row
                 pass
+            if lineages:
+                raise ValueError
             hit.update({"taxid": int(taxid)})
         except ValueError:
-            # no taxid in file
-            hit.update({"taxid": 0})
+            # no taxid in file, try matching accession to a lineage
+            hit.update(
+                {"taxid": accessions.get(taxid, accessions.get(hit["subject"], 0))}
+            )
         if bitscores:
             blastp[query] = hit
         else:
@@ -101,6 +124,40 @@ def parse_blast(blast_file, cols, results=None, index=0, evalue=1, bitscore=1):
     return results
 
 
+def remap_taxids(blast, taxdump, counts=None):
+    """Remap merged taxids and count hits to taxids missing from the taxdump."""
+    if counts is None:
+        counts = defaultdict(lambda: {"remapped": 0, "deleted": 0, "dropped": 0})
+    for hits in blast.values():
+        for hit in hits:
+            taxid = hit["taxid"]
+            if taxid not in taxdump.ancestors:
+                taxid = taxdump.update_taxid(taxid)
+                if taxid in taxdump.ancestors:
+                    hit["taxid"] = taxid
+                    counts[hit["file"]]["remapped"] += 1
+                else:
+                    if taxid in taxdump.deleted:
+                        counts[hit["file"]]["deleted"] += 1
+                    counts[hit["file"]]["dropped"] += 1
+    return counts
+
+
+def report_taxids(counts, files):
+    """Warn about remapped and dropped hits for each file."""
+    for index, file in enumerate(files):
+        if counts[index]["remapped"]:
+            print(
+                "WARN: %d hits in %s were remapped from merged taxids."
+                % (counts[index]["remapped"], file)
+            )
+        if counts[index]["dropped"]:
+            print(
+                "WARN: %d hits in %s had taxids missing from the taxdump (%d deleted)."
+                % (counts[index]["dropped"], file, counts[index]["deleted"])
+            )
+
+
 def chunk_size(value):
     """Calculate nice value for chunk size."""
     mag = math.floor(math.log10(value))
@@ -114,22 +171,13 @@ def set_windows(meta, taxrule):
     windows = []
     chunk = 0.1
     try:
-        if "bestsum" in taxrule:
+        if "bestsum" in taxrule or taxrule == "lca":
             chunk = 1
         elif "blast_max_chunks" in meta.settings:
             chunk = 1 / int(meta.settings["blast_max_chunks"])
         if "stats_windows" in meta.settings:
             for x in meta.settings["stats_windows"]:
-                window = {
-                    "key": ("%f" % x).rstrip("0").rstrip("."),
-                    "window": True,
-                    "value": float(x),
-                    "chunk": False,
-                }
-                if window["key"] == "0.1":
-                    window["title"] = "windows"
-                else:
-                    window["title"] = "windows_%s" % window["key"]
+                window = {**window_setting(x), "window": True, "chunk": False}
                 if chunk is not None and float(x) == chunk:
                     window.update({"chunk": True})
                     chunk = None
@@ -139,7 +187,7 @@ def set_windows(meta, taxrule):
     if chunk is not None:
         windows.append(
             {
-                "key": ("%f" % chunk).rstrip("0").rstrip("."),
+                "key": window_setting(chunk)["key"],
                 "window": False,
                 "value": chunk,
                 "chunk": True,
@@ -148,6 +196,13 @@ def set_windows(meta, taxrule):
     return windows
 
 
+def bin_size(length, value):
+    """Calculate the size of bins used to group hits along a sequence."""
+    if length < 1000000:
+        return 100000
+    return window_size(length, value)
+
+
 def bin_hits(
     blast,
     meta,
@@ -165,16 +220,9 @@ def bin_hits(
         length = lengths[idx]
         hits = sorted(blast[identifier], key=get_item)
         for window in windows:
-            if window["value"] > 1:
-                chunk = window["value"]
-            elif window["value"] == 1:
-                chunk = length
-            else:
-                chunk = round(length * window["value"] / 1000 + 0.5) * 1000
-            if length < 1000000:
-                if not window["chunk"]:
-                    continue
-                chunk = 100000
+            if length < 1000000 and not window["chunk"]:
+                continue
+            chunk = bin_size(length, window["value"])
             bin = {**window}
             groups = {
                 k: list(g)
@@ -211,6 +259,9 @@ def initialise_values(bins, prefix, results, ranks):
                 "data": {
                     "cindex": blank[:],
                     "score": blank[:],
+                    "confidence": blank[:],
+                    "runnerup": blank[:],
+                    "runnerup_score": blank[:],
                     "positions": blank[:],
                     "hits": blank[:],
                 },
@@ -224,10 +275,15 @@ def initialise_values(bins, prefix, results, ranks):
             "category": defaultdict(str),
             "cindex": defaultdict(int),
             "score": defaultdict(float),
+            "confidence": defaultdict(float),
+            "runnerup": defaultdict(str),
+            "runnerup_score": defaultdict(float),
             "positions": defaultdict(list),
             "categories": defaultdict(list),
             "scores": defaultdict(list),
+            "cat_scores": defaultdict(list),
             "hits": defaultdict(list),
+            "lineages": defaultdict(dict),
         }
         for window in windows:
             value.update({window: defaultdict(list)})
@@ -235,23 +291,81 @@ def initialise_values(bins, prefix, results, ranks):
     return results, values
 
 
-def apply_taxrule_to_bin(seqid, values, bin, window, taxdump, ranks):
+def lowest_common_ancestor(bin, taxdump, ranks, fraction):
+    """
+    Find lowest common ancestor of hits within a fraction of the top bitscore.
+
+    Ranks below the deepest shared taxon are reported as undefined descendants of
+    that taxon and hits that disagree at every rank are reported as unresolved.
+    """
+    lca = {}
+    if not bin:
+        return lca
+    top = max(hit["score"] for hit in bin)
+    selected = [hit for hit in bin if hit["score"] >= top * fraction]
+    score = sum(hit["score"] for hit in selected)
+    selected = [hit for hit in selected if hit["taxid"] in taxdump.ancestors]
+    last = 0
+    for rank in ranks:
+        taxa = {taxdump.ancestors[hit["taxid"]][rank] for hit in selected}
+        if len(taxa) != 1:
+            break
+        taxon = taxa.pop()
+        if taxon > 0:
+            last = taxon
+            lca[rank] = (taxdump.names[taxon], score)
+        elif taxon < 0:
+            lca[rank] = ("%s-undef" % taxdump.names[-taxon], score)
+    fill = "%s-undef" % taxdump.names[last] if last else "unresolved"
+    for rank in ranks:
+        if rank not in lca:
+            lca[rank] = (fill, score)
+    return lca
+
+
+def hit_category(hit, taxdump, rank):
+    """Find the category of a single hit at a given rank."""
+    try:
+        category = taxdump.ancestors[hit["taxid"]][rank]
+    except KeyError:
+        category = 0
+    if category > 0:
+        return taxdump.names[category]
+    if category < 0:
+        return "%s-undef" % taxdump.names[-category]
+    return "undef"
+
+
+def undef_base(category):
+    """Strip the undefined suffix from a category name."""
+    return re.sub(r"-undef$", "", category)
+
+
+def lca_support(bin, taxdump, ranks, category):
+    """Sum hit scores in a bin by whether each hit agrees with an lca category."""
+    support = defaultdict(float)
+    base = undef_base(category)
+    for hit in bin:
+        lineage = [hit_category(hit, taxdump, rank) for rank in ranks]
+        if category != "unresolved" and base in map(undef_base, lineage):
+            support[category] += hit["score"]
+        elif lineage[-1] != "undef":
+            support[lineage[-1]] += hit["score"]
+    return support
+
+
+def apply_taxrule_to_bin(
+    seqid, values, bin, window, taxdump, ranks, taxrule=None, lca_fraction=0.9
+):
     """Apply taxrule to a single bin."""
+    lca = None
+    if taxrule == "lca":
+        lca = lowest_common_ancestor(bin, taxdump, ranks, lca_fraction)
     for index, rank in enumerate(ranks):
         cat_scores = defaultdict(float)
         for hit in bin:
-            try:
-                category = taxdump.ancestors[hit["taxid"]][rank]
-            except KeyError:
-                category = 0
-            if category > 0:
-                category = taxdump.names[category]
-            elif category < 0:
-                category = "%s-undef" % taxdump.names[-category]
-            else:
-                category = "undef"
-            # category = "no-hit"
-            if category != "undef":
+            category = hit_category(hit, taxdump, rank)
+            if lca is None and category != "undef":
                 cat_scores[category] += hit["score"]
             if index == 0 and window["chunk"]:
                 try:
@@ -270,7 +384,14 @@ def apply_taxrule_to_bin(seqid, values, bin, window, taxdump, ranks):
                     pass
             if window["chunk"]:
                 values[index]["positions"][seqid].append([category])
-        if cat_scores:
+        if lca is not None and rank in lca:
+            category, score = lca[rank]
+            cat_scores = lca_support(bin, taxdump, ranks[: index + 1], category)
+            if window["chunk"]:
+                values[index]["lineages"][seqid][category] = {
+                    undef_base(lca[parent][0]) for parent in ranks[: index + 1]
+                }
+        elif cat_scores:
             category = max(cat_scores, key=cat_scores.get)
             score = cat_scores[category]
         else:
@@ -281,6 +402,18 @@ def apply_taxrule_to_bin(seqid, values, bin, window, taxdump, ranks):
         if window["chunk"]:
             values[index]["categories"][seqid].append([category])
             values[index]["scores"][seqid].append(score)
+            values[index]["cat_scores"][seqid].append(cat_scores)
+
+
+def related_categories(first, second, lineages):
+    """Check whether two lca categories share a lineage."""
+    if first == "unresolved" or second == "unresolved":
+        return False
+    first_lineage = lineages.get(first, {undef_base(first)})
+    second_lineage = lineages.get(second, {undef_base(second)})
+    return (
+        undef_base(first) in second_lineage or undef_base(second) in first_lineage
+    )
 
 
 def apply_taxrule_across_bins(seqid, values, window, ranks):
@@ -311,6 +444,32 @@ def apply_taxrule_across_bins(seqid, values, window, ranks):
                 values[index]["category"][seqid] = top_cat
                 values[index]["cindex"][seqid] = len(counts.keys()) - 1
                 values[index]["score"][seqid] = max_score
+                support = defaultdict(float)
+                for cat_scores in values[index]["cat_scores"][seqid]:
+                    for category, score in cat_scores.items():
+                        support[category] += score
+                total = sum(support.values())
+                if total:
+                    values[index]["confidence"][seqid] = float(
+                        "%.4f" % (support[top_cat] / total)
+                    )
+                lineages = values[index]["lineages"][seqid]
+                runnerup = sorted(
+                    [
+                        (score, cat)
+                        for cat, score in support.items()
+                        if cat != top_cat
+                        and not (
+                            lineages and related_categories(cat, top_cat, lineages)
+                        )
+                    ],
+                    reverse=True,
+                )
+                if runnerup:
+                    values[index]["runnerup_score"][seqid] = runnerup[0][0]
+                    values[index]["runnerup"][seqid] = runnerup[0][1]
+                else:
+                    values[index]["runnerup"][seqid] = "none"
 
 
 def add_values_to_results(results, values, ranks, identifiers):
@@ -328,6 +487,8 @@ def add_values_to_results(results, values, ranks, identifiers):
                     results[index]["data"]["cindex"][i] = values[index]["cindex"][
                         seq_id
                     ]
+                    for key in ("confidence", "runnerup", "runnerup_score"):
+                        results[index]["data"][key][i] = values[index][key][seq_id]
                     results[index]["data"]["positions"][i] = values[index]["positions"][
                         seq_id
                     ]
@@ -339,15 +500,24 @@ def add_values_to_results(results, values, ranks, identifiers):
                     results[index]["values"][i] = "no-hit"
                     results[index]["data"]["score"][i] = 0
                     results[index]["data"]["cindex"][i] = 0
-                    results[index]["data"]["positions"][i] = []
+                    results[index]["data"]["confidence"][i] = 0
+                    results[index]["data"]["runnerup"][i] = "no-hit"
+                    results[index]["data"]["runnerup_score"][i] = 0
+                    results[index]["data"]["positions"][i] = values[index][
+                        "positions"
+                    ].get(seq_id, [])
                     if index == 0:
-                        results[index]["data"]["hits"][i] = []
+                        results[index]["data"]["hits"][i] = values[index]["hits"].get(
+                            seq_id, []
+                        )
                 for key in values[index].keys():
                     if key.startswith("windows"):
                         results[index]["data"][key][i] = values[index][key][seq_id]
 
 
-def apply_taxrule(bins, taxdump, taxrule, prefix, results, identifiers):
+def apply_taxrule(
+    bins, taxdump, taxrule, prefix, results, identifiers, lca_fraction=0.9
+):
     """Apply taxrule to binned BLAST results."""
     ranks = taxdump.list_ranks()
     # ranks = ["superkingdom"]
@@ -355,16 +525,20 @@ def apply_taxrule(bins, taxdump, taxrule, prefix, results, identifiers):
     for seqid, windows in bins.items():
         for key, window in windows.items():
             for bin in window["bins"]:
-                apply_taxrule_to_bin(seqid, values, bin, window, taxdump, ranks)
+                apply_taxrule_to_bin(
+                    seqid, values, bin, window, taxdump, ranks, taxrule, lca_fraction
+                )
             apply_taxrule_across_bins(seqid, values, window, ranks)
     add_values_to_results(results, values, ranks, identifiers)
     return results
 
 
-def create_fields(results, taxrule, files, fields=None):
+def create_fields(results, taxrule, files, fields=None, taxid_counts=None):
     """Store BLAST results as Fields."""
     if fields is None:
         fields = []
+    if taxid_counts is None:
+        taxid_counts = defaultdict(lambda: {"remapped": 0, "deleted": 0, "dropped": 0})
     hits_id = "%s_%s" % (taxrule, "positions")
     fields.append(
         MultiArray(
@@ -378,6 +552,15 @@ def create_fields(results, taxrule, files, fields=None):
                 "preload": False,
                 "active": False,
                 "files": files,
+                "taxid_remapped": [
+                    taxid_counts[index]["remapped"] for index, _ in enumerate(files)
+                ],
+                "taxid_deleted": [
+                    taxid_counts[index]["deleted"] for index, _ in enumerate(files)
+                ],
+                "taxid_dropped": [
+                    taxid_counts[index]["dropped"] for index, _ in enumerate(files)
+                ],
             },
             parents=["children", {"id": taxrule}, "children"],
             category_slot=None,
@@ -438,6 +621,56 @@ def create_fields(results, taxrule, files, fields=None):
                 parents=parents,
             )
         )
+        field_id = "%s_%s" % (result["field_id"], "confidence")
+        fields.append(
+            Variable(
+                field_id,
+                values=result["data"]["confidence"],
+                meta={
+                    "scale": "scaleLinear",
+                    "field_id": field_id,
+                    "name": field_id,
+                    "datatype": "float",
+                    "range": [0, 1],
+                    "preload": False,
+                    "active": False,
+                },
+                parents=parents,
+            )
+        )
+        field_id = "%s_%s" % (result["field_id"], "runnerup")
+        fields.append(
+            Category(
+                field_id,
+                values=result["data"]["runnerup"],
+                meta={
+                    "field_id": field_id,
+                    "name": field_id,
+                    "preload": False,
+                    "active": False,
+                },
+                parents=parents,
+            )
+        )
+        field_id = "%s_%s" % (result["field_id"], "runnerup_score")
+        _min = min(result["data"]["runnerup_score"])
+        fields.append(
+            Variable(
+                field_id,
+                values=result["data"]["runnerup_score"],
+                meta={
+                    "scale": "scaleLog",
+                    "field_id": field_id,
+                    "name": field_id,
+                    "clamp": 1 if _min == 0 else False,
+                    "datatype": "float",
+                    "range": [_min, max(result["data"]["runnerup_score"])],
+                    "preload": False,
+                    "active": False,
+                },
+                parents=parents,
+            )
+        )
         subfield = "positions"
         field_id = "%s_%s" % (result["field_id"], subfield)
         if len(result["data"][subfield]) > 1:
@@ -492,12 +725,48 @@ def create_fields(results, taxrule, files, fields=None):
     return fields
 
 
-def parse(files, **kwargs):
-    """Parse BLAST results into Fields."""
-    blast = None
-    fields = []
+def assign_taxonomy(blasts, files, taxrule, prefix, **kwargs):
+    """Apply a taxrule to parsed hits from one or more files and create Fields."""
     identifiers = kwargs["dependencies"]["identifiers"]
     lengths = kwargs["dependencies"]["length"].values
+    lca_fraction = float(kwargs.get("--lca-fraction") or 0.9)
+    if not taxrule.endswith("order"):
+        merged = defaultdict(list)
+        for blast in blasts:
+            for seq_id, hits in blast.items():
+                merged[seq_id] += hits
+        blasts = [merged]
+    results = None
+    taxid_counts = None
+    for blast in blasts:
+        taxid_counts = remap_taxids(blast, kwargs["taxdump"], taxid_counts)
+        bins = bin_hits(
+            blast,
+            kwargs["meta"],
+            identifiers,
+            lengths,
+            taxrule,
+            int(kwargs["--hit-count"]),
+        )
+        results = apply_taxrule(
+            bins,
+            kwargs["taxdump"],
+            taxrule,
+            prefix,
+            results,
+            identifiers,
+            lca_fraction,
+        )
+    fields = create_fields(results, prefix, files, taxid_counts=taxid_counts)
+    report_taxids(taxid_counts, files)
+    update_plot = kwargs.get("--update-plot", False)
+    if update_plot or "cat" not in kwargs["meta"].plot:
+        kwargs["meta"].plot.update({"cat": "%s_phylum" % prefix})
+    return fields
+
+
+def parse(files, **kwargs):
+    """Parse BLAST results into Fields."""
     try:
         taxrule, prefix = kwargs["--taxrule"].split("=")
     except ValueError:
@@ -511,58 +780,21 @@ def parse(files, **kwargs):
             cols[name] = int(index) - 1
         except ValueError:
             exit("ERROR: --hits-cols contains an invalid value.")
-    if taxrule.endswith("order"):
-        results = None
-        for index, file in enumerate(files):
-            blast = parse_blast(
+    blasts = []
+    for index, file in enumerate(files):
+        blasts.append(
+            parse_blast(
                 file,
                 cols,
                 None,
                 index,
                 float(kwargs["--evalue"]),
                 float(kwargs["--bitscore"]),
+                kwargs["taxdump"].accessions,
+                os.path.isfile(kwargs["taxdump"].directory),
             )
-            bins = bin_hits(
-                blast,
-                kwargs["meta"],
-                identifiers,
-                lengths,
-                taxrule,
-                int(kwargs["--hit-count"]),
-            )
-            results = apply_taxrule(
-                bins, kwargs["taxdump"], taxrule, prefix, results, identifiers
-            )
-        fields = create_fields(results, prefix, files)
-    else:
-        results = None
-        previous = None
-        for index, file in enumerate(files):
-            blast = parse_blast(
-                file,
-                cols,
-                previous,
-                index,
-                float(kwargs["--evalue"]),
-                float(kwargs["--bitscore"]),
-            )
-            previous = blast
-        bins = bin_hits(
-            blast,
-            kwargs["meta"],
-            identifiers,
-            lengths,
-            taxrule,
-            int(kwargs["--hit-count"]),
-        )
-        results = apply_taxrule(
-            bins, kwargs["taxdump"], taxrule, prefix, results, identifiers
         )
-        fields = create_fields(results, prefix, files)
-    update_plot = kwargs.get("--update-plot", False)
-    if update_plot or "cat" not in kwargs["meta"].plot:
-        kwargs["meta"].plot.update({"cat": "%s_phylum" % prefix})
-    return fields
+    return assign_taxonomy(blasts, files, taxrule, prefix, **kwargs)
 
 
 def parent():
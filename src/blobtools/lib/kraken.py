@@ -0,0 +1,85 @@
+#!/usr/bin/env python3
+
+"""Parse Kraken2 and Centrifuge per-sequence classifications into taxrule Fields."""
+
+import re
+from collections import defaultdict
+
+from ..lib import file_io
+from ..lib import hits
+from .lca import assign_classifications
+
+
+def parse_taxid(string):
+    """Parse a taxid, allowing for Kraken2 --use-names output."""
+    match = re.search(r"\(taxid (\d+)\)\s*$", string)
+    if match:
+        return int(match.group(1))
+    return int(string)
+
+
+def parse_kraken_file(kraken_file, index=0):
+    """
+    Parse per-sequence taxids into hits.
+
+    Kraken2 --output files have status, sequence, taxid, length and LCA mapping
+    columns, each classified sequence is treated as a single hit with a score of 1.
+    Centrifuge output files have a readID header and a score column, sequences
+    may be assigned to several taxa.
+    """
+    results = defaultdict(list)
+    file_format = None
+    for line in file_io.stream_file(kraken_file):
+        row = line.rstrip("\n").split("\t")
+        if file_format is None:
+            if row[0] == "readID":
+                file_format = "centrifuge"
+                continue
+            file_format = "kraken"
+        if file_format == "kraken":
+            if len(row) < 3 or row[0] != "C":
+                continue
+            seq_id, taxid, score = row[1], row[2], 1
+        else:
+            if len(row) < 4:
+                continue
+            seq_id, taxid, score = row[0], row[2], row[3]
+        try:
+            taxid = parse_taxid(taxid)
+            score = float(score)
+        except ValueError:
+            continue
+        if taxid == 0:
+            continue
+        seq_id, *offset = seq_id.split("_-_")
+        offset = int(offset[0]) if offset else 0
+        results[seq_id].append(
+            {
+                "subject": file_format,
+                "score": score,
+                "start": offset,
+                "end": offset,
+                "file": index,
+                "taxid": taxid,
+            }
+        )
+    return file_format, results
+
+
+def parse(files, **kwargs):
+    """Parse all Kraken2/Centrifuge files."""
+    parsed = []
+    for file in files:
+        file, *prefix = file.split("=")
+        file_format, results = parse_kraken_file(file)
+        if file_format is None:
+            print("WARNING file %s is empty" % file)
+            continue
+        prefix = prefix[0] if prefix else file_format
+        parsed += assign_classifications(results, file, prefix, **kwargs)
+    return parsed
+
+
+def parent():
+    """Set standard metadata for Kraken2/Centrifuge classifications."""
+    return hits.parent()
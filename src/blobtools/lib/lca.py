@@ -0,0 +1,77 @@
+#!/usr/bin/env python3
+
+"""Parse Diamond and MMseqs2 LCA classifications into taxrule Fields."""
+
+from collections import defaultdict
+from pathlib import Path
+
+from ..lib import file_io
+from ..lib import hits
+
+
+def parse_classification(lca_file, index=0):
+    """
+    Parse per-query LCA taxids into hits.
+
+    Diamond --outfmt 102 files have query, taxid and evalue columns, MMseqs2
+    easy-taxonomy _lca.tsv files have query, taxid, rank and name columns.
+    Each classified query is treated as a single hit with a score of 1.
+    """
+    results = defaultdict(list)
+    file_format = None
+    for line in file_io.stream_file(lca_file):
+        row = line.rstrip("\n").split("\t")
+        if len(row) < 2:
+            continue
+        if file_format is None:
+            file_format = "mmseqs" if len(row) > 3 else "diamond"
+        try:
+            taxid = int(row[1])
+        except ValueError:
+            continue
+        if taxid == 0:
+            continue
+        seq_id, *offset = row[0].split("_-_")
+        offset = int(offset[0]) if offset else 0
+        results[seq_id].append(
+            {
+                "subject": row[3] if file_format == "mmseqs" else file_format,
+                "score": 1,
+                "start": offset,
+                "end": offset,
+                "file": index,
+                "taxid": taxid,
+            }
+        )
+    return file_format, results
+
+
+def assign_classifications(results, lca_file, prefix, **kwargs):
+    """Assign taxonomy to classifications using the bestsum taxrule."""
+    hit_count = max([len(seq_hits) for seq_hits in results.values()] + [1])
+    return hits.assign_taxonomy(
+        [results],
+        [str(Path(lca_file))],
+        "bestsum",
+        prefix,
+        **{**kwargs, "--hit-count": hit_count}
+    )
+
+
+def parse(files, **kwargs):
+    """Parse all LCA classification files."""
+    parsed = []
+    for file in files:
+        file, *prefix = file.split("=")
+        file_format, results = parse_classification(file)
+        if file_format is None:
+            print("WARNING file %s is empty" % file)
+            continue
+        prefix = prefix[0] if prefix else "%s_lca" % file_format
+        parsed += assign_classifications(results, file, prefix, **kwargs)
+    return parsed
+
+
+def parent():
+    """Set standard metadata for LCA classifications."""
+    return hits.parent()
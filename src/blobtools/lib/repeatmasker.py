@@ -0,0 +1,141 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""Parse RepeatMasker .out files into Variable Fields."""
+
+import re
+from collections import defaultdict
+
+from ..lib import file_io
+from .bed import fraction
+from .bed import intervals_to_windows
+from .bed import list_windows
+from .bed import merge_intervals
+from .bed import validate_range
+from .bed import window_fractions
+from .bed import window_size
+from .bed import windows_field
+from .field import Variable
+
+CLASSES = {
+    "LINE": "line",
+    "SINE": "sine",
+    "LTR": "ltr",
+    "DNA": "dna",
+    "Simple_repeat": "simple",
+    "Low_complexity": "simple",
+}
+
+NAMES = {
+    "all": "Repeat fraction",
+    "line": "LINE fraction",
+    "sine": "SINE fraction",
+    "ltr": "LTR fraction",
+    "dna": "DNA fraction",
+    "simple": "Simple repeat fraction",
+}
+
+
+def parse_repeatmasker_file(rm_file):
+    """Parse repeat intervals by class from a RepeatMasker .out file."""
+    intervals = defaultdict(lambda: defaultdict(list))
+    for line in file_io.stream_file(rm_file):
+        row = re.split(r"\s+", line.strip())
+        if len(row) < 11:
+            continue
+        try:
+            start, end = int(row[5]), int(row[6])
+        except ValueError:
+            continue
+        seq_id = row[4]
+        repeat_class = CLASSES.get(row[10].split("/")[0])
+        intervals[seq_id]["all"].append((start, end))
+        if repeat_class is not None:
+            intervals[seq_id][repeat_class].append((start, end))
+    return {
+        seq_id: {key: merge_intervals(spans) for key, spans in classes.items()}
+        for seq_id, classes in intervals.items()
+    }
+
+
+def parse_repeatmasker(rm_file, identifiers, lengths, windows):
+    """Parse a RepeatMasker .out file into Variable and windowed MultiArray Fields."""
+    rm_file, *prefix = rm_file.split("=")
+    prefix = prefix[0] if prefix else "repeats"
+    intervals = parse_repeatmasker_file(rm_file)
+    if not identifiers.validate_list(list(intervals.keys())):
+        raise UserWarning(
+            "Contig names in the RepeatMasker file did not match dataset identifiers."
+        )
+    values = defaultdict(list)
+    window_values = defaultdict(lambda: defaultdict(list))
+    for seq_id, length in zip(identifiers.values, lengths):
+        classes = intervals.get(seq_id, {})
+        for key in NAMES:
+            spans = classes.get(key, [])
+            values[key].append(
+                fraction(sum(end - start + 1 for start, end in spans), length)
+            )
+            for window in windows:
+                size = window_size(length, window["value"])
+                covered = intervals_to_windows(spans, length, size)
+                window_values[window["title"]][key].append(
+                    window_fractions(covered, length, size)
+                )
+    fields = []
+    for key, name in NAMES.items():
+        field_id = "%s_fraction" % prefix
+        if key != "all":
+            field_id = "%s_%s_fraction" % (prefix, key)
+        meta = {
+            "field_id": field_id,
+            "name": "%s %s" % (prefix, name),
+            "scale": "scaleLinear",
+            "datatype": "float",
+            "range": [min(values[key]), max(values[key])],
+            "preload": False,
+            "active": False,
+            "file": rm_file,
+        }
+        validate_range(meta)
+        fields.append(
+            Variable(field_id, meta=meta, values=values[key], parents=["children"])
+        )
+        for window in windows:
+            fields.append(
+                windows_field(
+                    field_id,
+                    meta["name"],
+                    window,
+                    window_values[window["title"]][key],
+                    ["children"],
+                )
+            )
+    return fields
+
+
+def parse(files, **kwargs):
+    """Parse all RepeatMasker files."""
+    parsed = []
+    windows = list_windows(kwargs["meta"])
+    for file in files:
+        parsed += parse_repeatmasker(
+            file,
+            identifiers=kwargs["dependencies"]["identifiers"],
+            lengths=kwargs["dependencies"]["length"].values,
+            windows=windows,
+        )
+    return parsed
+
+
+def parent():
+    """Set standard metadata for repeat annotations."""
+    repeats = {
+        "datatype": "float",
+        "type": "variable",
+        "scale": "scaleLinear",
+        "id": "repeat_annotation",
+        "name": "Repeat annotation",
+    }
+    return [repeats]
@@ -0,0 +1,140 @@
+#!/usr/bin/env python3
+
+# pylint: disable=no-member, too-many-locals
+
+"""
+Reassign taxonomy using hits stored in a BlobDir.
+
+Usage:
+    blobtools retaxrule --taxdump DIRECTORY [--source PREFIX] [--taxrule STRING]
+                        [--hit-count INT] [--bitscore FLOAT] [--lca-fraction FLOAT]
+                        [--update-plot] [--replace] DIRECTORY
+
+Arguments:
+    DIRECTORY             Existing Blob directory.
+
+Options:
+    --taxdump DIRECTORY   Location of NCBI new_taxdump directory or GTDB/custom lineage
+                          file.
+    --source PREFIX       Prefix of an existing taxrule with a <prefix>_positions field.
+                          Defaults to the taxrule used for the current plot category.
+    --taxrule rulename[=prefix]
+                          Rule to use when assigning hits to taxa (bestsum, bestsumorder,
+                          bestdistsum, bestdistsumorder, lca).
+                          An alternate prefix may be specified. [Default: bestsumorder]
+    --hit-count INT       Number of hits to use when inferring taxonomy. [Default: 10]
+    --bitscore FLOAT      Set bitscore cutoff for stored hits. [Default: 1]
+    --lca-fraction FLOAT  Minimum fraction of the top hit bitscore for a hit to be included
+                          when finding the lowest common ancestor (lca taxrule). [Default: 0.9]
+    --update-plot         Flag to use new taxrule as default category.
+    --replace             Replace existing fields with matching ids.
+
+Stored hits were already limited to the hit count used when they were first added
+and evalues are not stored, so only stricter hit count and bitscore cutoffs can be
+applied.
+
+Examples:
+    # 1. Reassign stored bestsumorder hits using the lca taxrule
+    blobtools retaxrule --source bestsumorder --taxrule lca --taxdump taxdump BlobDir
+
+"""
+
+import re
+import sys
+from collections import defaultdict
+
+from docopt import docopt
+
+from ..lib import file_io
+from ..lib import hits
+from .add import has_field_warning
+from .fetch import fetch_field
+from .fetch import fetch_metadata
+from .fetch import fetch_taxdump
+from .version import __version__
+
+
+def stored_hits(field, identifiers, file_count, bitscore):
+    """Rebuild per-file hit lists from a stored positions field."""
+    blasts = [defaultdict(list) for _ in range(file_count)]
+    for seq_id, seq_hits in zip(identifiers.values, field.values):
+        for taxid, start, end, score, subject, index, *title in seq_hits:
+            if score < bitscore:
+                continue
+            while index >= len(blasts):
+                blasts.append(defaultdict(list))
+            hit = {
+                "taxid": taxid,
+                "start": start,
+                "end": end,
+                "score": score,
+                "subject": subject,
+                "file": index,
+            }
+            if title and title[0] is not None:
+                hit["title"] = title[0]
+            blasts[index][seq_id].append(hit)
+    return blasts
+
+
+def main(args):
+    """Entrypoint for blobtools retaxrule."""
+    meta = fetch_metadata(args["DIRECTORY"], **args)
+    source = args["--source"]
+    if source is None:
+        source = re.sub(r"_[^_]+$", "", meta.plot.get("cat", ""))
+    hits_id = "%s_positions" % source
+    if not meta.has_field(hits_id):
+        print("ERROR: '%s' was not found in the BlobDir." % hits_id)
+        sys.exit(1)
+    dependencies = {}
+    for dep in ["identifiers", "length", hits_id]:
+        dependencies[dep] = fetch_field(args["DIRECTORY"], dep, meta)
+        if not dependencies[dep]:
+            print("ERROR: '%s.json' was not found in the BlobDir." % dep)
+            sys.exit(1)
+    try:
+        taxrule, prefix = args["--taxrule"].split("=")
+    except ValueError:
+        taxrule = args["--taxrule"]
+        prefix = taxrule
+    files = meta.field_meta(hits_id).get("files", [])
+    blasts = stored_hits(
+        dependencies[hits_id],
+        dependencies["identifiers"],
+        len(files),
+        float(args["--bitscore"]),
+    )
+    taxdump = fetch_taxdump(args["--taxdump"])
+    parsed = hits.assign_taxonomy(
+        blasts,
+        files,
+        taxrule,
+        prefix,
+        **args,
+        taxdump=taxdump,
+        dependencies=dependencies,
+        meta=meta
+    )
+    parents = hits.parent()
+    for data in parsed:
+        if not args["--replace"]:
+            if has_field_warning(meta, data.field_id):
+                continue
+        meta.add_field(parents + data.parents, **data.meta)
+        json_file = "%s/%s.json" % (args["DIRECTORY"], data.field_id)
+        file_io.write_file(json_file, data.values_to_dict())
+    file_io.write_file("%s/meta.json" % args["DIRECTORY"], meta.to_dict())
+
+
+def cli():
+    """Entry point."""
+    if len(sys.argv) == sys.argv.index(__name__.split(".")[-1]) + 1:
+        args = docopt(__doc__, argv=[])
+    else:
+        args = docopt(__doc__, version=__version__)
+    main(args)
+
+
+if __name__ == "__main__":
+    cli()
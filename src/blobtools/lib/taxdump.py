@@ -3,14 +3,62 @@
 
 import os
 import re
+import sqlite3
+from collections.abc import Mapping
 
 from ..lib import file_io
 
 
+class TaxdumpIndex(Mapping):
+    """Read-only mapping backed by a table in an SQLite taxdump index."""
+
+    def __init__(self, connection, table, key="taxid", decode=None):
+        """Init TaxdumpIndex class."""
+        self.connection = connection
+        self.table = table
+        self.key = key
+        self.decode = decode
+        self.cache = {}
+
+    def __getitem__(self, key):
+        """Fetch a value from the index."""
+        if key in self.cache:
+            return self.cache[key]
+        row = self.connection.execute(
+            "SELECT value FROM %s WHERE %s = ?" % (self.table, self.key), (key,)
+        ).fetchone()
+        if row is None:
+            raise KeyError(key)
+        value = self.decode(row[0]) if self.decode is not None else row[0]
+        self.cache[key] = value
+        return value
+
+    def __iter__(self):
+        """Iterate over all keys in the index."""
+        for row in self.connection.execute(
+            "SELECT %s FROM %s" % (self.key, self.table)
+        ):
+            yield row[0]
+
+    def __len__(self):
+        """Count entries in the index."""
+        return self.connection.execute(
+            "SELECT COUNT(*) FROM %s" % self.table
+        ).fetchone()[0]
+
+
 class Taxdump:
     """Class for working with NCBI taxonomy."""
 
-    __slots__ = ["directory", "ancestors", "names", "ranks"]
+    __slots__ = [
+        "directory",
+        "ancestors",
+        "names",
+        "ranks",
+        "accessions",
+        "merged",
+        "deleted",
+    ]
 
     def __init__(self, directory, **kwargs):
         """Init Taxdump class."""
@@ -18,17 +66,32 @@ class Taxdump:
         self.ancestors = {}
         self.ranks = {}
         self.names = {}
+        self.accessions = {}
+        self.merged = {}
+        self.deleted = set()
         if kwargs:
             self.update_data(**kwargs)
+        elif self.index_is_current(directory):
+            self.load_index()
+        elif os.path.isfile(directory):
+            self.load_lineages()
         else:
             self.load_ranks()
             self.load_names()
             self.load_ancestors()
+            self.load_merged()
 
     def update_data(self, **kwargs):
         """Update values and keys for an existing field."""
         for key, value in kwargs.items():
-            setattr(self, key, {int(taxid): data for taxid, data in value.items()})
+            if key == "accessions":
+                setattr(self, key, {acc: int(taxid) for acc, taxid in value.items()})
+            elif key == "deleted":
+                setattr(self, key, {int(taxid) for taxid in value})
+            elif key == "merged":
+                setattr(self, key, {int(old): int(new) for old, new in value.items()})
+            else:
+                setattr(self, key, {int(taxid): data for taxid, data in value.items()})
 
     def load_ranks(self):
         """Load ranks from file."""
@@ -71,12 +134,157 @@ class Taxdump:
                     else:
                         self.ancestors[taxid].update({rank: last})
 
+    def load_merged(self):
+        """Load merged and deleted taxids from file."""
+        filename = os.path.abspath(os.path.join(self.directory, "merged.dmp"))
+        if os.path.isfile(filename):
+            for line in file_io.stream_file(filename):
+                row = self.parse_taxdump_row(line)
+                if len(row) > 1:
+                    self.merged[int(row[0])] = int(row[1])
+        filename = os.path.abspath(os.path.join(self.directory, "delnodes.dmp"))
+        if os.path.isfile(filename):
+            for line in file_io.stream_file(filename):
+                row = self.parse_taxdump_row(line)
+                if row:
+                    self.deleted.add(int(row[0]))
+
+    def update_taxid(self, taxid):
+        """Follow merged taxids to their current taxid."""
+        seen = set()
+        while taxid in self.merged and taxid not in seen:
+            seen.add(taxid)
+            taxid = self.merged[taxid]
+        return taxid
+
+    def load_lineages(self):
+        """
+        Load GTDB-style lineages from a file.
+
+        Each row contains an accession and a semicolon-separated lineage
+        (e.g. d__Bacteria;p__Proteobacteria;...;s__Escherichia coli).
+        Synthetic taxids are assigned to each node in the lineage.
+        """
+        prefixes = {
+            "d": "superkingdom",
+            "k": "kingdom",
+            "p": "phylum",
+            "c": "class",
+            "o": "order",
+            "f": "family",
+            "g": "genus",
+            "s": "species",
+        }
+        nodes = {}
+        for line in file_io.stream_file(self.directory):
+            row = line.rstrip("\n").split("\t")
+            if len(row) < 2 or "__" not in row[1]:
+                continue
+            path = []
+            taxid = 0
+            ancestors = {}
+            for taxon in row[1].split(";"):
+                try:
+                    prefix, name = taxon.strip().split("__", 1)
+                except ValueError:
+                    continue
+                if not name or prefix not in prefixes:
+                    continue
+                path.append(taxon.strip())
+                node = ";".join(path)
+                if node not in nodes:
+                    nodes[node] = len(nodes) + 1
+                    self.names[nodes[node]] = name
+                    self.ranks[nodes[node]] = prefixes[prefix]
+                taxid = nodes[node]
+                ancestors[prefixes[prefix]] = taxid
+                if taxid not in self.ancestors:
+                    self.ancestors[taxid] = dict(ancestors)
+            if taxid:
+                self.accessions[row[0]] = taxid
+        for ancestors in self.ancestors.values():
+            last = 0
+            for rank in self.list_ranks():
+                if rank in ancestors:
+                    last = -ancestors[rank]
+                else:
+                    ancestors.update({rank: last})
+
+    def load_index(self):
+        """Load ancestors, names, ranks and accessions from an SQLite index."""
+        connection = sqlite3.connect(self.index_file(self.directory))
+        ranks = self.list_ranks()
+        self.ranks = TaxdumpIndex(connection, "ranks")
+        self.names = TaxdumpIndex(connection, "names")
+        self.ancestors = TaxdumpIndex(
+            connection,
+            "ancestors",
+            decode=lambda value: dict(zip(ranks, map(int, value.split(",")))),
+        )
+        self.accessions = TaxdumpIndex(connection, "accessions", key="accession")
+        tables = {
+            row[0]
+            for row in connection.execute(
+                "SELECT name FROM sqlite_master WHERE type = 'table'"
+            )
+        }
+        if "merged" in tables:
+            self.merged = TaxdumpIndex(connection, "merged", decode=int)
+            self.deleted = TaxdumpIndex(connection, "deleted")
+
+    def write_index(self, filename=None):
+        """Write ancestors, names, ranks and accessions to an SQLite index."""
+        if filename is None:
+            filename = self.index_file(self.directory)
+        file_io.delete_file(filename)
+        ranks = self.list_ranks()
+        with sqlite3.connect(filename) as connection:
+            for table in ("ranks", "names", "ancestors", "merged", "deleted"):
+                connection.execute(
+                    "CREATE TABLE %s (taxid INTEGER PRIMARY KEY, value TEXT)" % table
+                )
+            connection.execute(
+                "CREATE TABLE accessions (accession TEXT PRIMARY KEY, value INTEGER)"
+            )
+            connection.execute(
+                "CREATE TABLE sources (filename TEXT PRIMARY KEY, value TEXT)"
+            )
+            connection.executemany(
+                "INSERT INTO sources VALUES (?, ?)",
+                self.source_stats(self.directory).items(),
+            )
+            connection.executemany(
+                "INSERT INTO ranks VALUES (?, ?)", self.ranks.items()
+            )
+            connection.executemany(
+                "INSERT INTO names VALUES (?, ?)", self.names.items()
+            )
+            connection.executemany(
+                "INSERT INTO ancestors VALUES (?, ?)",
+                (
+                    (taxid, ",".join(str(ancestors[rank]) for rank in ranks))
+                    for taxid, ancestors in self.ancestors.items()
+                ),
+            )
+            connection.executemany(
+                "INSERT INTO accessions VALUES (?, ?)", self.accessions.items()
+            )
+            connection.executemany(
+                "INSERT INTO merged VALUES (?, ?)", self.merged.items()
+            )
+            connection.executemany(
+                "INSERT INTO deleted VALUES (?, ?)",
+                ((taxid, 1) for taxid in self.deleted),
+            )
+        return filename
+
     def values_to_dict(self):
         """Create a dict of values."""
         data = {}
-        for key in ("ancestors", "names", "ranks"):
+        for key in ("ancestors", "names", "ranks", "accessions", "merged"):
             if hasattr(self, key):
                 data[key] = getattr(self, key)
+        data["deleted"] = sorted(self.deleted)
         return data
 
     def lineage(self, taxid):
@@ -84,13 +292,67 @@ class Taxdump:
         lineages = {}
         try:
             ancestors = self.ancestors[taxid]
-        except ValueError:
+        except (KeyError, ValueError):
             return {}
         for rank in self.list_ranks():
             if rank in ancestors and ancestors[rank] > 0:
                 lineages.update({rank: self.names[ancestors[rank]]})
         return lineages
 
+    @staticmethod
+    def index_file(path):
+        """Return path to the SQLite index for a taxdump directory or lineage file."""
+        if os.path.isfile(path):
+            return "%s.taxdump.sqlite" % path
+        return os.path.join(path, "taxdump.sqlite")
+
+    @staticmethod
+    def source_stats(path):
+        """Return size and modification time of the files a taxdump is parsed from."""
+        if os.path.isfile(path):
+            filenames = [path]
+        else:
+            filenames = [
+                os.path.join(path, filename)
+                for filename in (
+                    "nodes.dmp",
+                    "names.dmp",
+                    "taxidlineage.dmp",
+                    "merged.dmp",
+                    "delnodes.dmp",
+                )
+            ]
+        stats = {}
+        for filename in filenames:
+            if os.path.isfile(filename):
+                stat = os.stat(filename)
+                stats[os.path.basename(filename)] = "%d:%d" % (
+                    stat.st_size,
+                    stat.st_mtime_ns,
+                )
+        return stats
+
+    @classmethod
+    def index_is_current(cls, path):
+        """Check an SQLite index exists and matches the files it was built from."""
+        index_file = cls.index_file(path)
+        if not os.path.isfile(index_file):
+            return False
+        stats = cls.source_stats(path)
+        if not stats:
+            # only the index is available so there is nothing to compare
+            return True
+        with sqlite3.connect(index_file) as connection:
+            tables = {
+                row[0]
+                for row in connection.execute(
+                    "SELECT name FROM sqlite_master WHERE type = 'table'"
+                )
+            }
+            if "sources" not in tables:
+                return False
+            return stats == dict(connection.execute("SELECT * FROM sources"))
+
     @staticmethod
     def parse_taxdump_row(line):
         """Parse an ncbi taxdump file."""
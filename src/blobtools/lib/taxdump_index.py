@@ -0,0 +1,47 @@
+#!/usr/bin/env python3
+
+"""
+Index a taxdump for faster loading.
+
+Usage:
+    blobtools taxdump index TAXDUMP
+
+Arguments:
+    TAXDUMP               NCBI new_taxdump directory or GTDB/custom lineage file.
+
+Examples:
+    # 1. Index an NCBI taxdump so later commands load it from taxdump/taxdump.sqlite
+    blobtools taxdump index taxdump
+
+"""
+
+import sys
+
+from docopt import docopt
+
+from ..lib import file_io
+from .taxdump import Taxdump
+from .version import __version__
+
+
+def main(args):
+    """Entrypoint for blobtools taxdump."""
+    if args["index"]:
+        index_file = Taxdump.index_file(args["TAXDUMP"])
+        file_io.delete_file(index_file)
+        print("Parsing taxdump")
+        taxdump = Taxdump(args["TAXDUMP"])
+        print("Writing taxdump index to %s" % taxdump.write_index(index_file))
+
+
+def cli():
+    """Entry point."""
+    if len(sys.argv) == sys.argv.index("taxdump") + 1:
+        args = docopt(__doc__, argv=[])
+    else:
+        args = docopt(__doc__, version=__version__)
+    main(args)
+
+
+if __name__ == "__main__":
+    cli()
@@ -0,0 +1,170 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""Find telomeric repeats at sequence ends."""
+
+from collections import Counter
+
+from ..lib import file_io
+from .field import Category
+from .field import Variable
+from .tetra import reverse_complement
+
+
+def rotations(motif):
+    """List all rotations of a repeat motif."""
+    return [motif[i:] + motif[:i] for i in range(len(motif))]
+
+
+def canonical_motif(motif):
+    """Choose a single representative of all rotations on both strands."""
+    return min(rotations(motif) + rotations(reverse_complement(motif)))
+
+
+def conventional_motif(motif):
+    """Orient a motif on its G-rich strand, ending with the G run (e.g. TTAGGG)."""
+    if motif.count("C") > motif.count("G"):
+        motif = reverse_complement(motif)
+    return max(rotations(motif))
+
+
+def is_low_complexity(motif):
+    """Check whether a motif is itself a shorter tandem repeat."""
+    return any(
+        motif == motif[i:] + motif[:i]
+        for i in range(1, len(motif))
+        if len(motif) % i == 0
+    )
+
+
+def detect_motif(fasta_file, end_length=200, kmer_range=(5, 8)):
+    """Detect a telomeric repeat from the most common terminal k-mer."""
+    best = (0, None)
+    counts = {k: Counter() for k in range(kmer_range[0], kmer_range[1] + 1)}
+    for _seq_id, seq_str in file_io.stream_fasta(fasta_file):
+        seq_str = seq_str.upper()
+        for end in (seq_str[:end_length], seq_str[-end_length:]):
+            for k, kmer_counts in counts.items():
+                kmers = Counter(end[i : i + k] for i in range(len(end) - k + 1))
+                for kmer, count in kmers.items():
+                    if "N" in kmer or is_low_complexity(kmer):
+                        continue
+                    kmer_counts[canonical_motif(kmer)] += count
+    for k, kmer_counts in counts.items():
+        if kmer_counts:
+            motif, count = kmer_counts.most_common(1)[0]
+            if count > best[0]:
+                best = (count, motif)
+    if best[1] is None:
+        return None
+    return conventional_motif(best[1])
+
+
+def telomere_length(seq_str, motif, max_misses=2):
+    """
+    Measure a tandem repeat of a motif from the start of a sequence.
+
+    Repeats may start in any phase within one motif length of the sequence
+    start and may be on either strand. Copies with a single mismatch are
+    accepted within a repeat, which ends at the last exact copy, and scanning
+    stops after max_misses consecutive non-matching copies. The phase with the
+    most copies is used, preferring the shortest span for ties.
+    """
+    size = len(motif)
+    best = (0, 0)
+    units = set(rotations(motif) + rotations(reverse_complement(motif)))
+    for offset in range(size):
+        for unit in units:
+            position = offset
+            end = 0
+            misses = 0
+            while position + size <= len(seq_str):
+                chunk = seq_str[position : position + size]
+                mismatches = sum(a != b for a, b in zip(chunk, unit))
+                if mismatches <= 1:
+                    if not mismatches:
+                        end = position + size
+                    misses = 0
+                else:
+                    misses += 1
+                    if misses > max_misses or not end:
+                        break
+                position += size
+            if end:
+                best = max(best, ((end - offset) // size, -end))
+    return -best[1]
+
+
+def telomere_fields(fasta_file, identifiers, **kwargs):
+    """Create telomere length and presence Fields."""
+    motif = kwargs["--telomere"].upper()
+    if motif == "AUTO":
+        motif = detect_motif(fasta_file)
+        if motif is None:
+            print("WARN: Unable to detect a telomeric repeat motif")
+            return []
+        print("Detected telomeric repeat motif %s" % motif)
+    min_length = int(kwargs.get("--telomere-min-length", 50))
+    window = int(kwargs.get("--telomere-window", 20000))
+    lengths = {}
+    for seq_id, seq_str in file_io.stream_fasta(fasta_file):
+        seq_str = seq_str.upper()
+        lengths[seq_id] = [
+            telomere_length(seq_str[:window], motif),
+            telomere_length(reverse_complement(seq_str[-window:]), motif),
+        ]
+    values = {"5p": [], "3p": []}
+    presence = []
+    for seq_id in identifiers.values:
+        five, three = lengths.get(seq_id, [0, 0])
+        values["5p"].append(five)
+        values["3p"].append(three)
+        ends = [
+            end
+            for end, length in zip(["5p", "3p"], [five, three])
+            if length >= min_length
+        ]
+        presence.append("both" if len(ends) == 2 else ends[0] if ends else "none")
+    parents = [
+        {
+            "id": "telomere",
+            "name": "Telomere",
+            "datatype": "integer",
+            "type": "variable",
+            "scale": "scaleLinear",
+        },
+        "children",
+    ]
+    meta = {
+        "motif": motif,
+        "min_length": min_length,
+        "preload": False,
+        "active": False,
+    }
+    fields = [
+        Category(
+            "telomere_ends",
+            meta={**meta, "field_id": "telomere_ends", "name": "Telomeric ends"},
+            values=presence,
+            parents=parents,
+        )
+    ]
+    for end, name in [("5p", "5'"), ("3p", "3'")]:
+        field_id = "telomere_%s_length" % end
+        fields.append(
+            Variable(
+                field_id,
+                meta={
+                    **meta,
+                    "field_id": field_id,
+                    "name": "%s telomere length" % name,
+                    "scale": "scaleLinear",
+                    "datatype": "integer",
+                    "range": [min(values[end]), max(values[end])],
+                },
+                values=values[end],
+                parents=parents,
+            )
+        )
+    return fields
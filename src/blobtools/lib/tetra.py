@@ -0,0 +1,148 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""Calculate tetranucleotide frequency Fields from FASTA sequences."""
+
+from collections import Counter
+from itertools import product
+
+from .field import MultiArray
+from .field import Variable
+
+COMPLEMENT = str.maketrans("ACGT", "TGCA")
+
+
+def reverse_complement(seq_str):
+    """Reverse complement a sequence."""
+    return seq_str.translate(COMPLEMENT)[::-1]
+
+
+def canonical_kmers(k=4):
+    """List canonical k-mers, merging each k-mer with its reverse complement."""
+    kmers = []
+    for kmer in ("".join(bases) for bases in product("ACGT", repeat=k)):
+        if kmer <= reverse_complement(kmer):
+            kmers.append(kmer)
+    return kmers
+
+
+KMERS = canonical_kmers()
+
+
+def tetranucleotide_frequencies(seq_str):
+    """Calculate canonical tetranucleotide frequencies for a sequence."""
+    seq_str = seq_str.upper()
+    counts = Counter(seq_str[i : i + 4] for i in range(len(seq_str) - 3))
+    canonical = Counter()
+    for kmer, count in counts.items():
+        canonical[min(kmer, reverse_complement(kmer))] += count
+    total = sum(canonical[kmer] for kmer in KMERS)
+    if not total:
+        return [0] * len(KMERS)
+    return [float("%.4g" % (canonical[kmer] / total)) for kmer in KMERS]
+
+
+def principal_components(profiles, count, iterations=100):
+    """
+    Find leading principal components by power iteration.
+
+    Returns column means and a list of unit eigenvectors.
+    """
+    dims = len(profiles[0])
+    means = [sum(column) / len(profiles) for column in zip(*profiles)]
+    cov = [[0.0] * dims for _ in range(dims)]
+    for profile in profiles:
+        centred = [value - mean for value, mean in zip(profile, means)]
+        for i, x_i in enumerate(centred):
+            if x_i:
+                cov[i] = [c + x_i * x_j for c, x_j in zip(cov[i], centred)]
+    components = []
+    for index in range(count):
+        vector = [1.0 / (i + index + 1) for i in range(dims)]
+        for _ in range(iterations):
+            vector = [sum(c * v for c, v in zip(row, vector)) for row in cov]
+            for component in components:
+                dot = sum(v * c for v, c in zip(vector, component))
+                vector = [v - dot * c for v, c in zip(vector, component)]
+            norm = sum(v * v for v in vector) ** 0.5
+            if not norm:
+                break
+            vector = [v / norm for v in vector]
+        components.append(vector)
+    return means, components
+
+
+def project(profile, means, component):
+    """Project a frequency profile onto a principal component."""
+    value = sum((p - m) * c for p, m, c in zip(profile, means, component))
+    return float("%.4g" % value)
+
+
+def tetra_fields(profiles, lengths, **kwargs):
+    """Create tetranucleotide frequency MultiArray and PCA Variable Fields."""
+    min_length = int(kwargs.get("--tetra-min-length", 1000))
+    training = [
+        profile
+        for profile, length in zip(profiles, lengths)
+        if length >= min_length and any(profile)
+    ]
+    if len(training) < 2:
+        training = [profile for profile in profiles if any(profile)]
+    parents = [
+        {
+            "id": "tetranucleotide",
+            "name": "Tetranucleotide composition",
+            "datatype": "float",
+            "type": "variable",
+            "scale": "scaleLinear",
+        },
+        "children",
+    ]
+    fields = [
+        MultiArray(
+            "tetra",
+            meta={
+                "field_id": "tetra",
+                "name": "Tetranucleotide frequencies",
+                "type": "multiarray",
+                "datatype": "float",
+                "preload": False,
+                "active": False,
+            },
+            values=[[profile] for profile in profiles],
+            headers=KMERS,
+            parents=parents,
+        )
+    ]
+    if not kwargs.get("--tetra-components"):
+        return fields
+    if len(training) < 2:
+        print("WARN: Too few sequences to calculate tetranucleotide PCA")
+        return fields
+    count = min(max(int(kwargs["--tetra-components"]), 2), 3)
+    means, components = principal_components(training, count)
+    for index, component in enumerate(components):
+        values = [
+            project(profile, means, component) if any(profile) else 0
+            for profile in profiles
+        ]
+        field_id = "tetra_pc%d" % (index + 1)
+        fields.append(
+            Variable(
+                field_id,
+                meta={
+                    "field_id": field_id,
+                    "name": "Tetranucleotide PC%d" % (index + 1),
+                    "scale": "scaleLinear",
+                    "datatype": "float",
+                    "range": [min(values), max(values)],
+                    "min_length": min_length,
+                    "preload": False,
+                    "active": False,
+                },
+                values=values,
+                parents=parents,
+            )
+        )
+    return fields
@@ -197,6 +197,16 @@ def check_expected_field_properties(field):
                 "expected %s.datatype to be 'float'" % field_id
             )
             assert field["range"][0] >= 0, "expected %s.range[0] to be >= 1" % field_id
+        elif re.match(r".+_confidence$", field_id):
+            assert field["type"] == "variable", (
+                "expected %s.type to be 'variable'" % field_id
+            )
+            assert field["datatype"] == "float", (
+                "expected %s.datatype to be 'float'" % field_id
+            )
+            assert field["range"][0] >= 0 and field["range"][1] <= 1, (
+                "expected %s.range to be within [0, 1]" % field_id
+            )
         else:
             assert field["type"] == "category", (
                 "expected %s.type to be 'category'" % field_id
@@ -0,0 +1,196 @@
+#!/usr/bin/env python3
+
+# pylint: disable=too-many-locals
+
+"""Parse VCF variant calls into heterozygosity Fields."""
+
+import re
+from collections import defaultdict
+from statistics import median
+
+from ..lib import file_io
+from .bed import list_windows
+from .bed import validate_range
+from .bed import window_size
+from .bed import window_spans
+from .bed import window_starts
+from .bed import windows_field
+from .field import Variable
+
+FIELDS = {
+    "het_count": {"name": "Heterozygous sites", "datatype": "integer"},
+    "homalt_count": {"name": "Homozygous alt sites", "datatype": "integer"},
+    "het_density": {"name": "Heterozygous sites per kb", "datatype": "float"},
+    "homalt_density": {"name": "Homozygous alt sites per kb", "datatype": "float"},
+    "allele_balance_mean": {"name": "Mean allele balance", "datatype": "float"},
+    "allele_balance_median": {"name": "Median allele balance", "datatype": "float"},
+}
+
+WINDOW_FIELDS = ["het_count", "homalt_count", "het_density", "homalt_density"]
+
+
+def parse_genotype(fmt, sample):
+    """Parse genotype alleles and allele balance from a sample column."""
+    data = dict(zip(fmt.split(":"), sample.split(":")))
+    alleles = re.split(r"[/|]", data.get("GT", "."))
+    if "." in alleles:
+        return None, None
+    balance = None
+    if len(set(alleles)) > 1 and "AD" in data:
+        try:
+            depths = [int(depth) for depth in data["AD"].split(",")]
+            ref, alt = depths[int(alleles[0])], depths[int(alleles[1])]
+            if ref + alt:
+                balance = alt / (ref + alt)
+        except (ValueError, IndexError):
+            pass
+    return alleles, balance
+
+
+def parse_vcf_file(vcf_file, sample=None):
+    """Parse heterozygous and homozygous alt sites from a VCF file."""
+    het = defaultdict(list)
+    homalt = defaultdict(list)
+    balance = defaultdict(list)
+    index = 9
+    for line in file_io.stream_file(vcf_file):
+        if line.startswith("##"):
+            continue
+        row = line.rstrip("\n").split("\t")
+        if line.startswith("#"):
+            if sample is not None:
+                if sample not in row[9:]:
+                    raise UserWarning(
+                        "Sample '%s' was not found in the VCF file." % sample
+                    )
+                index = row.index(sample)
+            continue
+        if len(row) <= index or row[6] not in ("PASS", "."):
+            continue
+        alleles, allele_balance = parse_genotype(row[8], row[index])
+        if alleles is None:
+            continue
+        if len(set(alleles)) > 1:
+            het[row[0]].append(int(row[1]))
+            if allele_balance is not None:
+                balance[row[0]].append(allele_balance)
+        elif alleles[0] != "0":
+            homalt[row[0]].append(int(row[1]))
+    return het, homalt, balance
+
+
+def site_stats(het, homalt, balance, length):
+    """Calculate variant statistics for a single sequence."""
+    return {
+        "het_count": len(het),
+        "homalt_count": len(homalt),
+        "het_density": float("%.4g" % (len(het) / length * 1000)) if length else 0,
+        "homalt_density": (
+            float("%.4g" % (len(homalt) / length * 1000)) if length else 0
+        ),
+        "allele_balance_mean": (
+            float("%.4f" % (sum(balance) / len(balance))) if balance else 0
+        ),
+        "allele_balance_median": float("%.4f" % median(balance)) if balance else 0,
+    }
+
+
+def site_window_stats(het, homalt, length, size):
+    """Calculate variant counts and densities for each window along a sequence."""
+    starts = window_starts(length, size)
+    stats = {}
+    for key, positions in (("het", het), ("homalt", homalt)):
+        counts = [0] * len(starts)
+        for position in positions:
+            counts[min(max(position - 1, 0) // size, len(starts) - 1)] += 1
+        stats["%s_count" % key] = counts
+        stats["%s_density" % key] = [
+            float("%.4g" % (count / span * 1000)) if span else 0
+            for count, span in zip(counts, window_spans(length, size))
+        ]
+    return stats
+
+
+def parse_vcf(vcf_file, identifiers, lengths, windows, sample=None):
+    """Parse a VCF file into Variable and windowed MultiArray Fields."""
+    vcf_file, *prefix = vcf_file.split("=")
+    prefix = prefix[0] if prefix else "vcf"
+    het, homalt, balance = parse_vcf_file(vcf_file, sample)
+    if not identifiers.validate_list(list(set(het.keys()) | set(homalt.keys()))):
+        raise UserWarning(
+            "Contig names in the VCF file did not match dataset identifiers."
+        )
+    values = defaultdict(list)
+    window_values = defaultdict(lambda: defaultdict(list))
+    for seq_id, length in zip(identifiers.values, lengths):
+        stats = site_stats(
+            het.get(seq_id, []), homalt.get(seq_id, []), balance.get(seq_id, []), length
+        )
+        for key, value in stats.items():
+            values[key].append(value)
+        for window in windows:
+            size = window_size(length, window["value"])
+            stats = site_window_stats(
+                het.get(seq_id, []), homalt.get(seq_id, []), length, size
+            )
+            for key, value in stats.items():
+                window_values[window["title"]][key].append(value)
+    fields = []
+    for key, settings in FIELDS.items():
+        field_id = "%s_%s" % (prefix, key)
+        meta = {
+            "field_id": field_id,
+            "name": "%s %s" % (prefix, settings["name"]),
+            "scale": "scaleLinear",
+            "datatype": settings["datatype"],
+            "range": [min(values[key]), max(values[key])],
+            "preload": False,
+            "active": False,
+            "file": vcf_file,
+        }
+        if sample is not None:
+            meta["sample"] = sample
+        validate_range(meta)
+        fields.append(
+            Variable(field_id, meta=meta, values=values[key], parents=["children"])
+        )
+        if key not in WINDOW_FIELDS:
+            continue
+        for window in windows:
+            fields.append(
+                windows_field(
+                    field_id,
+                    meta["name"],
+                    window,
+                    window_values[window["title"]][key],
+                    ["children"],
+                )
+            )
+    return fields
+
+
+def parse(files, **kwargs):
+    """Parse all VCF files."""
+    parsed = []
+    windows = list_windows(kwargs["meta"])
+    for file in files:
+        parsed += parse_vcf(
+            file,
+            identifiers=kwargs["dependencies"]["identifiers"],
+            lengths=kwargs["dependencies"]["length"].values,
+            windows=windows,
+            sample=kwargs.get("--vcf-sample"),
+        )
+    return parsed
+
+
+def parent():
+    """Set standard metadata for variant calls."""
+    variants = {
+        "datatype": "float",
+        "type": "variable",
+        "scale": "scaleLinear",
+        "id": "variants",
+        "name": "Variants",
+    }
+    return [variants]
@@ -0,0 +1,15 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import Metadata, bed
+
+
+def test_window_fractions():
+    assert bed.window_spans(2500, 1000) == [1000, 1000, 500]
+    assert bed.window_fractions([500, 0, 250], 2500, 1000) == [0.5, 0, 0.5]
+    assert bed.window_fractions([0], 0, 1) == [0]
+
+
+def test_list_windows():
+    meta = Metadata('test', settings={'stats_windows': [0.1, 0.01, 1, 100000]})
+    assert [window['title'] for window in bed.list_windows(meta)] == [
+        'windows', 'windows_0.01', 'windows_100000']
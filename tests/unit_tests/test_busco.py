@@ -0,0 +1,101 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import busco
+from lib.field import Identifier, MultiArray
+
+
+def busco_field(values):
+    return MultiArray('test_odb10_busco', values=values,
+                      headers=busco.HEADERS, category_slot=1)
+
+
+def test_haplotig_status_breaks_ties():
+    lengths = {'a': 100, 'b': 100, 'c': 50}
+    assert busco.haplotig_status('a', 'b', lengths, None) == 'primary'
+    assert busco.haplotig_status('b', 'a', lengths, None) == 'candidate'
+    covs = {'a': 10, 'b': 12, 'c': 11}
+    assert busco.haplotig_status('b', 'a', lengths, covs) == 'primary'
+    assert busco.haplotig_status('a', 'b', lengths, covs) == 'haplotig'
+    assert busco.haplotig_status('c', 'a', lengths, {**covs, 'c': 40}) == 'candidate'
+
+
+def test_haplotig_fields():
+    def shared():
+        return [['b1', 'Duplicated', 0, 0, '+', 0],
+                ['b2', 'Duplicated', 0, 0, '+', 0]]
+    identifiers = Identifier('identifiers', values=['a', 'b', 'c'])
+    fields = busco.haplotig_fields(
+        busco_field([shared(), shared(), [['b3', 'Complete', 0, 0, '+', 0]]]),
+        identifiers, [100, 100, 80], [10, 12, 11])
+    values = {field.field_id: field.expand_values() for field in fields
+              if field.field_id != 'test_odb10_duplicate_shared'}
+    assert values['test_odb10_duplicate_partner'] == ['b', 'a', 'none']
+    assert values['test_odb10_haplotig'] == ['haplotig', 'primary', 'none']
+
+
+def test_parse_compleasm_table(tmp_path):
+    directory = tmp_path / 'eukaryota_odb10'
+    directory.mkdir()
+    table = directory / 'full_table.tsv'
+    table.write_text(
+        'Gene\tStatus\tSequence\tGene Start\tGene End\tStrand\tScore\tLength\n'
+        'b1\tSingle\tc1\t2000\t1000\t-\t812.5\t300\n'
+        'b2\tDuplicated\tc1\t5000\t6000\t+\t400\t200\n'
+        'b2\tDuplicated\tc2\t100\t900\t+\t390\t200\n'
+        'b3\tIncomplete\tc2\t1\t50\t+\t10\t20\n'
+        'b4\tMissing\n'
+        'b5\tInterspersed\tc3\t10\t500\t+\t30\t120\n')
+    identifiers = Identifier('identifiers', values=['c1', 'c2', 'c3'])
+    field = busco.parse_busco(str(table), identifiers)
+    assert field.field_id == 'eukaryota_odb10_busco'
+    assert field.meta['version'] == 'compleasm'
+    assert field.meta['count'] == 5
+    assert field.expand_values() == [
+        [['b1', 'Complete', 1000, 2000, '-', 812.5],
+         ['b2', 'Duplicated', 5000, 6000, '+', 400]],
+        [['b2', 'Duplicated', 100, 900, '+', 390],
+         ['b3', 'Fragmented', 1, 50, '+', 10]],
+        [['b5', 'Fragmented', 10, 500, '+', 30]]]
+    scores = busco.busco_score(field.expand_values(), field.meta['count'])
+    assert (scores['c'], scores['d'], scores['f'], scores['m']) == (2, 1, 2, 1)
+
+
+def test_parse_busco_summary_json(tmp_path):
+    run = tmp_path / 'run_insecta_odb10'
+    run.mkdir()
+    (run / 'full_table.tsv').write_text(
+        '# BUSCO version is: 5.4.3\n'
+        '# Busco id\tStatus\tSequence\tGene Start\tGene End\tStrand\tScore\tLength\n'
+        'b1\tComplete\tc1\t10\t900\t+\t500\t300\n')
+    summary = tmp_path / 'short_summary.json'
+    summary.write_text(
+        '{"versions": {"busco": "5.4.3"}, "lineage_dataset": '
+        '{"name": "insecta_odb10", "number_of_buscos": "1367"}, '
+        '"results": {"one_line_summary": "C:99.0%[S:98.0%,D:1.0%]"}}')
+    identifiers = Identifier('identifiers', values=['c1'])
+    field = busco.parse_busco(str(summary), identifiers)
+    assert field.field_id == 'insecta_odb10_busco'
+    assert field.meta['count'] == 1367
+    assert field.meta['summary'] == 'C:99.0%[S:98.0%,D:1.0%]'
+    assert field.expand_values() == [[['b1', 'Complete', 10, 900, '+', 500]]]
+
+
+def test_busco_window_fields():
+    windows = [{'key': '0.1', 'value': 0.1, 'title': 'windows'},
+               {'key': '100000', 'value': 100000.0, 'title': 'windows_100000'}]
+    field = busco_field([
+        [['b1', 'Complete', 1, 1000, '+', 1],
+         ['b2', 'Duplicated', 14000, 16000, '-', 1],
+         ['b3', 'Fragmented', 19500, 20500, '+', 1],
+         ['b4', 'Complete', 0, 0, '.', 0]],
+        []])
+    fields = busco.busco_window_fields(field, [20000, 500], windows)
+    assert [window.field_id for window in fields] == [
+        'test_odb10_busco_windows', 'test_odb10_busco_windows_100000']
+    assert fields[0].expand_values() == [
+        [['b1', 'Complete', 0], ['b2', 'Duplicated', 7],
+         ['b3', 'Fragmented', 9]], []]
+    assert fields[1].expand_values()[0] == [
+        ['b1', 'Complete', 0], ['b2', 'Duplicated', 0], ['b3', 'Fragmented', 0]]
+    assert busco.gene_window(1, 1, 2000, 10) == 0
+    assert busco.gene_window(1, 40000, 2000, 10) == 9
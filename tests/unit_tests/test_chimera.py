@@ -0,0 +1,34 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import chimera
+
+SETTINGS = {'min_windows': 2, 'min_gc_shift': 0.05, 'min_cov_ratio': 1.5}
+
+
+def test_category_runs_skips_unassigned_and_short_runs():
+    categories = ['A', 'A', 'no-hit', 'A', 'B', 'A', 'A', 'unresolved', 'C', 'C']
+    runs = chimera.category_runs(categories, 10, 95, 2)
+    assert [(run['category'], run['count'], run['start'], run['end'])
+            for run in runs] == [('A', 5, 0, 70), ('C', 2, 80, 95)]
+    assert runs[0]['last_start'] == 60
+    assert runs[1]['first_end'] == 90
+
+
+def test_find_switches_with_support():
+    taxonomy = (['A', 'A', 'A', 'B', 'B', 'B'], 10)
+    stats = {'gc': ([0.3, 0.3, 0.3, 0.5, 0.5, 0.5], 10),
+             'cov': ([10, 10, 10, 12, 12, 12], 10)}
+    switches = chimera.find_switches('c1', 60, taxonomy, stats, **SETTINGS)
+    assert len(switches) == 1
+    switch = switches[0]
+    assert (switch['breakpoint'], switch['start'], switch['end']) == (30, 20, 40)
+    assert switch['before']['gc'] == 0.3
+    assert switch['support'] == ['gc']
+    assert chimera.switch_status(switches) == 'taxonomy+gc'
+
+
+def test_find_switches_ignores_single_window_noise():
+    taxonomy = (['A', 'A', 'B', 'A', 'A'], 10)
+    switches = chimera.find_switches('c1', 50, taxonomy, {}, **SETTINGS)
+    assert switches == []
+    assert chimera.switch_status(switches) == 'none'
@@ -0,0 +1,114 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+import math
+
+import pytest
+
+from lib import Metadata, cov, file_io
+from lib.field import Identifier, MultiArray, Variable
+
+
+def test_fit_gc_model():
+    pairs = [(0.3, 10)] * 10 + [(0.5, 20)] * 10 + [(0.7, 30)] * 2
+    model = cov.fit_gc_model(pairs)
+    assert model[30] == 10
+    assert model[50] == 20
+    assert model[70] == 20
+    assert cov.fit_gc_model(pairs[:5]) is None
+
+
+def windows(field_id, values):
+    return MultiArray(field_id, values=[[[value] for value in seq_values]
+                                        for seq_values in values],
+                      meta={'field_id': field_id}, headers=[field_id[:-8]])
+
+
+def test_gc_corrected_fields(tmp_path):
+    meta = Metadata('test', fields=[{'id': 'gc_windows', 'type': 'multiarray'}])
+    gc_windows = windows('gc_windows', [[0.3] * 10 + [None], [0.5] * 10, []])
+    file_io.write_file('%s/gc_windows.json' % tmp_path,
+                       gc_windows.values_to_dict())
+    raw = Variable('lib_cov', values=[10, 20, 7], meta={'field_id': 'lib_cov'})
+    fields = cov.gc_corrected_fields(
+        raw, [windows('lib_cov_windows', [[10] * 10 + [99], [20] * 10, []])],
+        meta=meta, DIRECTORY=str(tmp_path),
+        dependencies={'length': Variable('length', values=[20000, 20000, 500])})
+    assert fields[0].field_id == 'lib_gc_corrected_cov'
+    assert fields[0].values == [15, 15, 7]
+
+
+def test_filtered_coverage_settings():
+    assert cov.reference_length('10S50M2D20M5I3N') == 72
+    settings = cov.filter_settings(**{'--cov-min-mapq': '10',
+                                      '--cov-proper-pairs': True})
+    assert settings == {'min_mapq': 10, 'include_flags': 2, 'exclude_flags': 0,
+                        'proper_pairs': True}
+
+
+def test_filtered_coverage_rejects_gc_correct():
+    identifiers = Variable('identifiers', values=['c1'])
+    with pytest.raises(SystemExit):
+        cov.parse_bam('reads.bam=reads', **{'--cov-min-mapq': '10',
+                                            '--cov-gc-correct': True},
+                      dependencies={'identifiers': identifiers})
+
+
+def test_depth_formats():
+    assert cov.depth_format('sample.regions.bed.gz') == ('.regions.bed.gz',
+                                                         'intervals')
+    assert cov.depth_format('sample.BW') == ('.bw', 'bigwig')
+    assert cov.depth_format('sample.bam') == (None, None)
+    assert cov.base_names(['a/sample.mosdepth.summary.txt', 'b.bam']) == [
+        'a/sample.mosdepth.summary.txt=sample', 'b.bam=b']
+
+
+def test_parse_mosdepth_summary(tmp_path):
+    summary = tmp_path / 'sample.mosdepth.summary.txt'
+    summary.write_text('chrom\tlength\tbases\tmean\tmin\tmax\n'
+                       'c1\t100\t1000\t10.00\t0\t20\n'
+                       'c1_region\t100\t1000\t10.00\t0\t20\n'
+                       'total\t100\t1000\t10.00\t0\t20\n')
+    assert cov.parse_mosdepth_summary(str(summary)) == {'c1': 10}
+
+
+def test_parse_bedgraph_depth(tmp_path):
+    bedgraph = tmp_path / 'sample.bedgraph'
+    bedgraph.write_text('track type=bedGraph\nc1\t0\t1500\t4\nc1\t1500\t2000\t8\n'
+                        'c2\t0\t500\t2\n')
+    fields = cov.parse_depth(
+        '%s=sample' % bedgraph, cov_range=[math.inf, -math.inf],
+        meta=Metadata('test', settings={'stats_windows': [0.1]}),
+        dependencies={'identifiers': Identifier('identifiers',
+                                                values=['c1', 'c2']),
+                      'length': Variable('length', values=[2000, 1000])})
+    assert fields['cov'].field_id == 'sample_cov'
+    assert fields['cov'].values == [5, 1]
+    assert fields['windows'][0].field_id == 'sample_cov_windows'
+    assert fields['windows'][0].values == [[[4.0], [6.0]], [[1.0]]]
+
+
+def test_parse_paf(tmp_path):
+    paf = tmp_path / 'reads.paf'
+    paf.write_text(
+        'r1\t5000\t0\t1000\t+\tc1\t2000\t0\t1000\t990\t1000\t60\ttp:A:P\n'
+        'r2\t5000\t0\t1000\t+\tc1\t2000\t500\t1500\t990\t1000\t60\ttp:A:P\n'
+        'r2\t5000\t0\t1000\t+\tc1\t2000\t500\t1500\t990\t1000\t60\ttp:A:S\n'
+        'r3\t5000\t0\t500\t+\tc2\t1000\t0\t500\t490\t500\t0\ttp:A:P\n'
+        'r4\t5000\t0\t200\t+\tc2\t1000\t0\t200\t190\t200\t60\ttp:A:P\n')
+    intervals, reads, counts = cov.parse_paf_file(str(paf), min_mapq=10)
+    assert intervals == {'c1': [(0, 1000, 1), (500, 1500, 1)], 'c2': [(0, 200, 1)]}
+    assert reads == {'c1': {'r1', 'r2'}, 'c2': {'r4'}}
+    assert counts == {'alignments': 4, 'filtered': 1}
+    fields = cov.parse_paf(
+        '%s=reads' % paf, cov_range=[math.inf, -math.inf],
+        read_cov_range=[math.inf, -math.inf],
+        meta=Metadata('test', settings={'stats_windows': [0.1]}),
+        dependencies={'identifiers': Identifier('identifiers',
+                                                values=['c1', 'c2']),
+                      'length': Variable('length', values=[2000, 1000])},
+        **{'--cov-min-mapq': '10', '--cov-min-length': '0'})
+    assert fields['cov'].values == [1, 0.2]
+    assert fields['read_cov'].field_id == 'reads_read_cov'
+    assert fields['read_cov'].values == [2, 1]
+    assert fields['cov'].meta['filtered'] == 1
+    assert fields['windows'][0].values == [[[1.5], [0.5]], [[0.2]]]
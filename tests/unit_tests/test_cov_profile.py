@@ -0,0 +1,38 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+import pytest
+
+from lib import Metadata, cov, cov_profile
+from lib.field import Variable
+
+
+def test_clr():
+    values = cov_profile.clr([0.99, 0.99, 9.99])
+    assert sum(values) == pytest.approx(0)
+    assert values[2] - values[0] == pytest.approx(2.302585)
+
+
+def test_profile_fields():
+    fields = cov_profile.profile_fields([
+        Variable('b_cov', values=[10, 20, 5, 40]),
+        Variable('a_cov', values=[10, 5, 20, 40]),
+    ])
+    fields = {field.field_id: field for field in fields}
+    assert fields['coverage_profile'].values[1] == [5, 20]
+    assert fields['coverage_profile'].meta['headers'] == ['a', 'b']
+    assert fields['a_b_log_ratio'].values == [0, -1.9978, 1.9978, 0]
+    assert list(fields) == ['coverage_profile', 'a_b_log_ratio',
+                            'coverage_profile_pc1']
+    pc1 = fields['coverage_profile_pc1'].values
+    assert pc1[0] == pytest.approx(pc1[3], abs=1e-3)
+    assert pc1[1] == pytest.approx(-pc1[2], abs=1e-3)
+
+
+def test_coverage_profile_uses_base_coverage_only():
+    parsed = [Variable('a_cov', values=[1, 2]), Variable('a_read_cov', values=[5, 5]),
+              Variable('a_gc_corrected_cov', values=[1, 2]),
+              Variable('b_cov', values=[2, 1])]
+    fields = cov.coverage_profile(parsed, meta=Metadata('test'), DIRECTORY='')
+    assert fields[0].meta['headers'] == ['a', 'b']
+    assert cov.coverage_profile(parsed[:3], meta=Metadata('test'),
+                                DIRECTORY='') == []
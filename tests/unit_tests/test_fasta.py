@@ -0,0 +1,24 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import Metadata, fasta
+
+
+def test_base_composition():
+    assert fasta.base_composition('ACGTacgtNNNRYnnGC') == {
+        'gc': 0.6, 'ncount': 7, 'masked': 0.3529, 'gaps': 2, 'gapspan': 5,
+        'iupac': 2}
+
+
+def test_parse_composition_fields(tmp_path):
+    fasta_file = tmp_path / 'assembly.fa'
+    fasta_file.write_text('>c1\nACGTACGTNNNNACGT\n>c2\nacgtRYacgt\n')
+    fields = fasta.parse(str(fasta_file), dependencies={'identifiers': None},
+                         meta=Metadata('test'))
+    fields = {field.field_id: field for field in fields}
+    assert fields['identifiers'].values == ['c1', 'c2']
+    assert fields['length'].values == [16, 10]
+    assert fields['masked'].values == [0, 0.8]
+    assert fields['gaps'].values == [1, 0]
+    assert fields['gapspan'].values == [4, 0]
+    assert fields['iupac'].values == [0, 2]
+    assert fields['iupac'].meta['range'] == [0, 2]
@@ -0,0 +1,30 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import fcs_gx
+from lib.field import Identifier
+
+REPORT = '''##[["FCS genome report", 2, 1]]
+#seq_id\tstart_pos\tend_pos\tseq_len\taction\tdiv\tagg_cont_cov\ttop_tax_name
+c1\t1\t2000\t10000\tTRIM\tprok:CFB group bacteria\t20\tFlavobacterium
+c1\t6001\t8000\t10000\tREVIEW\tprok:CFB group bacteria\t20\tFlavobacterium
+c2\t1\t500\t500\tEXCLUDE\tprok:firmicutes\t100\tBacillus
+'''
+
+
+def test_parse_fcs_gx(tmp_path):
+    report = tmp_path / 'fcs_gx_report.txt'
+    report.write_text(REPORT)
+    identifiers = Identifier('identifiers', values=['c1', 'c2', 'c3'])
+    windows = [{'title': 'windows', 'key': '0.1', 'value': 0.1}]
+    fields = fcs_gx.parse_fcs_gx(str(report), identifiers, [10000, 500, 100],
+                                 windows)
+    fields = {field.field_id: field for field in fields}
+    assert fields['fcs_gx_action'].expand_values() == ['TRIM', 'EXCLUDE', 'none']
+    assert fields['fcs_gx_division'].expand_values()[1] == 'prok:firmicutes'
+    assert fields['fcs_gx_coverage'].values == [20, 100, 0]
+    assert fields['fcs_gx_trim'].values == [0.2, 0, 0]
+    assert fields['fcs_gx_ranges'].expand_values()[0] == [
+        [1, 2000, 'TRIM', 'prok:CFB group bacteria'],
+        [6001, 8000, 'REVIEW', 'prok:CFB group bacteria']]
+    trim = fields['fcs_gx_trim_windows'].values[0]
+    assert [value[0] for value in trim] == [1, 0, 0, 0, 0]
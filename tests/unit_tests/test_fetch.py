@@ -0,0 +1,46 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+import os
+
+from lib import Taxdump, file_io, hits, taxdump_index
+from lib.fetch import fetch_taxdump
+
+
+def test_fetch_taxdump_adds_merged_to_old_cache(_taxdump_dir):
+    directory = _taxdump_dir
+    taxdump = fetch_taxdump(directory)
+    cache = file_io.load_yaml('%s/taxdump.json' % directory)
+    del cache['merged']
+    del cache['deleted']
+    file_io.write_file('%s/taxdump.json' % directory, cache)
+    taxdump = fetch_taxdump(directory)
+    assert taxdump.merged == {8: 4}
+    assert taxdump.deleted == {9}
+    assert file_io.load_yaml('%s/taxdump.json' % directory)['merged'] == {'8': 4}
+    blast = {'c1': [{'taxid': 8, 'file': 0}, {'taxid': 9, 'file': 0},
+                    {'taxid': 4, 'file': 0}]}
+    counts = hits.remap_taxids(blast, taxdump)
+    assert [hit['taxid'] for hit in blast['c1']] == [4, 9, 4]
+    assert counts[0] == {'remapped': 1, 'deleted': 1, 'dropped': 1}
+
+
+def test_taxdump_index_skips_json_cache(_taxdump_dir):
+    directory = _taxdump_dir
+    taxdump_index.main({'index': True, 'TAXDUMP': directory})
+    assert not os.path.exists('%s/taxdump.json' % directory)
+    assert Taxdump.index_is_current(directory)
+    assert fetch_taxdump(directory).names[4] == 'Escherichia coli'
+
+
+def test_fetch_taxdump_rebuilds_stale_index(_taxdump_dir):
+    directory = _taxdump_dir
+    Taxdump(directory).write_index()
+    names = '%s/names.dmp' % directory
+    with open(names) as fh:
+        text = fh.read()
+    with open(names, 'w') as fh:
+        fh.write(text.replace('Escherichia coli', 'Escherichia albertii'))
+    assert not Taxdump.index_is_current(directory)
+    assert fetch_taxdump(directory).names[4] == 'Escherichia albertii'
+    assert Taxdump.index_is_current(directory)
+    assert Taxdump(directory).names[4] == 'Escherichia albertii'
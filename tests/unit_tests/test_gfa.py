@@ -0,0 +1,27 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import Metadata, gfa
+from lib.field import Identifier
+
+GFA = '''H\tVN:Z:1.0
+S\tc1\t*\tLN:i:5000
+S\tc2\t*\tLN:i:3000
+S\tc3\t*\tLN:i:2000
+S\tc4\t*\tLN:i:1000
+L\tc1\t+\tc2\t-\t0M
+L\tc2\t+\tc3\t+\t0M
+L\tc4\t+\tc4\t-\t0M
+'''
+
+
+def test_parse_gfa(tmp_path):
+    gfa_file = tmp_path / 'assembly.gfa'
+    gfa_file.write_text(GFA)
+    identifiers = Identifier('identifiers', values=['c1', 'c2', 'c3', 'c4', 'c5'])
+    fields = gfa.parse_gfa(str(gfa_file), identifiers, Metadata('test'),
+                           str(tmp_path))
+    fields = {field.field_id: field for field in fields}
+    assert fields['gfa_component'].expand_values() == [
+        'component_1', 'component_1', 'component_1', 'unlinked', 'none']
+    assert fields['gfa_degree'].values == [1, 2, 1, 0, 0]
+    assert fields['gfa_links'].expand_values()[1] == [['c1'], ['c3']]
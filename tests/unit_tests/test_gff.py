@@ -0,0 +1,38 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import gff
+
+GFF3 = '''##gff-version 3
+c1\t.\tgene\t1\t1000\t.\t+\t.\tID=gene:g1;gene_id=g1
+c1\t.\tmRNA\t1\t1000\t.\t+\t.\tID=transcript:t1;Parent=gene:g1;gene_id=g1
+c1\t.\tCDS\t101\t400\t.\t+\t0\tID=cds:t1;Parent=transcript:t1;gene_id=g1
+c1\t.\tgene\t2001\t3000\t.\t-\t.\tID=gene:g2
+c1\t.\tCDS\t2201\t2500\t.\t-\t0\tID=cds:t2;Parent=gene:g2
+'''
+
+GTF = '''c1\t.\ttranscript\t1\t1000\t.\t+\t.\tgene_id "g1"; transcript_id "t1";
+c1\t.\tCDS\t101\t400\t.\t+\t0\tgene_id "g1"; transcript_id "t1";
+c1\t.\ttranscript\t501\t1500\t.\t+\t.\tgene_id "g1"; transcript_id "t2";
+c2\t.\ttranscript\t2001\t3000\t.\t-\t.\tgene_id "g2"; transcript_id "t3";
+c2\t.\tCDS\t2201\t2500\t.\t-\t0\tgene_id "g2"; transcript_id "t3";
+'''
+
+
+def test_parse_gff3_counts_gene_features(tmp_path):
+    gff_file = tmp_path / 'genes.gff3'
+    gff_file.write_text(GFF3)
+    genes, cds = gff.parse_gff_file(str(gff_file))
+    assert genes == {'c1': [[1, 1000], [2001, 3000]]}
+    assert cds == {'c1': [[101, 400], [2201, 2500]]}
+    stats = gff.gene_stats(genes['c1'], cds['c1'], 4000)
+    assert stats['gene_count'] == 2
+    assert stats['coding_fraction'] == 0.15
+    assert stats['mean_gene_length'] == 1000
+
+
+def test_parse_gtf_uses_gene_ids(tmp_path):
+    gtf_file = tmp_path / 'genes.gtf'
+    gtf_file.write_text(GTF)
+    genes, cds = gff.parse_gff_file(str(gtf_file))
+    assert genes == {'c1': [[1, 1500]], 'c2': [[2001, 3000]]}
+    assert cds == {'c1': [[101, 400]], 'c2': [[2201, 2500]]}
@@ -0,0 +1,154 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+import pytest
+
+from lib import Metadata, Taxdump, bed, hits
+from lib.field import Identifier, Variable
+
+RANKS = Taxdump.list_ranks()
+NAMES = {1: 'Eukaryota', 2: 'Bacteria', 3: 'Arthropoda', 4: 'Chordata',
+         5: 'Insecta', 6: 'Arachnida'}
+LINEAGES = {
+    10: {'superkingdom': 1, 'phylum': 3, 'class': 5},
+    11: {'superkingdom': 1, 'phylum': 3, 'class': 6},
+    12: {'superkingdom': 1, 'phylum': 4},
+    20: {'superkingdom': 2},
+}
+
+
+def ancestors(lineage):
+    values = {}
+    last = 0
+    for rank in RANKS:
+        if rank in lineage:
+            values[rank] = lineage[rank]
+            last = -lineage[rank]
+        else:
+            values[rank] = last
+    return values
+
+
+@pytest.fixture(scope='function', name='_my_taxdump')
+def my_taxdump():
+    return Taxdump('/dev/null',
+                   ancestors={taxid: ancestors(lineage)
+                              for taxid, lineage in LINEAGES.items()},
+                   names=NAMES, ranks={})
+
+
+def hit(taxid, score, start=1):
+    return {'taxid': taxid, 'score': score, 'start': start, 'end': start + 99,
+            'subject': 'subject_%d' % taxid, 'file': 0}
+
+
+def test_lca_agreement(_my_taxdump):
+    lca = hits.lowest_common_ancestor(
+        [hit(10, 100), hit(10, 95)], _my_taxdump, RANKS, 0.9)
+    assert lca['class'] == ('Insecta', 195)
+    assert lca['genus'] == ('Insecta-undef', 195)
+
+
+def test_lca_disagreement_at_mid_rank(_my_taxdump):
+    lca = hits.lowest_common_ancestor(
+        [hit(10, 100), hit(11, 95)], _my_taxdump, RANKS, 0.9)
+    assert lca['superkingdom'] == ('Eukaryota', 195)
+    assert lca['phylum'] == ('Arthropoda', 195)
+    assert lca['class'] == ('Arthropoda-undef', 195)
+
+
+def test_lca_disagreement_at_superkingdom(_my_taxdump):
+    lca = hits.lowest_common_ancestor(
+        [hit(10, 100), hit(20, 95)], _my_taxdump, RANKS, 0.9)
+    assert lca['superkingdom'] == ('unresolved', 195)
+    assert lca['species'] == ('unresolved', 195)
+
+
+def test_lca_unknown_taxids(_my_taxdump):
+    lca = hits.lowest_common_ancestor([hit(99, 100)], _my_taxdump, RANKS, 0.9)
+    assert lca['phylum'] == ('unresolved', 100)
+
+
+def test_lca_fraction(_my_taxdump):
+    bin_hits = [hit(10, 100), hit(12, 85)]
+    lca = hits.lowest_common_ancestor(bin_hits, _my_taxdump, RANKS, 0.9)
+    assert lca['class'] == ('Insecta', 100)
+    lca = hits.lowest_common_ancestor(bin_hits, _my_taxdump, RANKS, 0.8)
+    assert lca['phylum'] == ('Eukaryota-undef', 185)
+
+
+def assign(blast, taxdump, taxrule, length=5000):
+    identifiers = Identifier('identifiers', values=['c1', 'c2'])
+    dependencies = {'identifiers': identifiers,
+                    'length': Variable('length', values=[length, length])}
+    return hits.assign_taxonomy(
+        [blast], ['hits.tsv'], taxrule, taxrule, taxdump=taxdump,
+        dependencies=dependencies, meta=Metadata('test'),
+        **{'--hit-count': 10, '--lca-fraction': 0.9})
+
+
+def test_lca_keeps_unresolved_hits(_my_taxdump):
+    blast = {'c1': [hit(10, 100), hit(20, 100, 200)], 'c2': [hit(12, 50)]}
+    fields = {field.field_id: field for field in assign(blast, _my_taxdump, 'lca')}
+    assert fields['lca_phylum'].expand_values() == ['unresolved', 'Chordata']
+    assert fields['lca_phylum_score'].values == [200, 50]
+    assert len(fields['lca_positions'].values[0]) == 2
+
+
+def test_bestsum_confidence_and_runnerup(_my_taxdump):
+    blast = {'c1': [hit(10, 100), hit(12, 50, 200)], 'c2': [hit(12, 50)]}
+    fields = {field.field_id: field
+              for field in assign(blast, _my_taxdump, 'bestsum')}
+    assert fields['bestsum_phylum'].expand_values() == ['Arthropoda', 'Chordata']
+    assert fields['bestsum_phylum_confidence'].values == [0.6667, 1]
+    assert fields['bestsum_phylum_runnerup'].expand_values() == ['Chordata', 'none']
+    assert fields['bestsum_phylum_runnerup_score'].values == [50, 0]
+    assert fields['bestsum_phylum_runnerup_score'].meta['clamp'] == 1
+
+
+def test_lca_confidence_uses_hits_in_bin(_my_taxdump):
+    blast = {'c1': [hit(10, 100), hit(12, 50, 200)], 'c2': [hit(10, 100), hit(20, 100)]}
+    fields = {field.field_id: field for field in assign(blast, _my_taxdump, 'lca')}
+    assert fields['lca_phylum'].expand_values() == ['Arthropoda', 'unresolved']
+    assert fields['lca_phylum_confidence'].values == [0.6667, 0]
+    assert fields['lca_phylum_runnerup'].expand_values() == [
+        'Chordata', 'Bacteria-undef']
+    assert fields['lca_phylum_runnerup_score'].values == [50, 100]
+
+
+def test_lca_runnerup_excludes_lineage(_my_taxdump):
+    blast = {'c1': [hit(10, 100), hit(10, 100, 100001), hit(10, 100, 200001),
+                    hit(11, 100, 200101), hit(12, 50, 300001)],
+             'c2': [hit(12, 50)]}
+    fields = {field.field_id: field
+              for field in assign(blast, _my_taxdump, 'lca', length=400000)}
+    assert fields['lca_class'].expand_values()[0] == 'Insecta'
+    assert fields['lca_class_confidence'].values[0] == 0.4444
+    assert fields['lca_class_runnerup'].expand_values()[0] == 'Chordata-undef'
+    assert fields['lca_class_runnerup_score'].values[0] == 50
+
+
+def test_windows_match_bed_windows():
+    meta = Metadata('test', settings={'stats_windows': [0.1, 0.01, 1, 100000]})
+    windows = [window for window in hits.set_windows(meta, 'bestsumorder')
+               if window['value'] != 1]
+    assert [{key: window[key] for key in ('key', 'value', 'title')}
+            for window in windows] == bed.list_windows(meta)
+    assert hits.bin_size(2500000, 0.1) == bed.window_size(2500000, 0.1)
+    assert hits.bin_size(500000, 0.1) == 100000
+
+
+def test_lineage_taxdump_matches_accessions(tmp_path):
+    lineages = tmp_path / 'lineages.tsv'
+    lineages.write_text('acc1\td__Bacteria;p__Proteobacteria\n'
+                        'acc2\td__Eukaryota;p__Chordata\n')
+    taxdump = Taxdump(str(lineages))
+    blast = tmp_path / 'hits.tsv'
+    blast.write_text(
+        'c1\t2\t200\tx\tacc2\t0\t0\t0\t0\t1\t100\t0\t0\t1e-10\n'
+        'c2\tacc1\t200\tx\ts1\t0\t0\t0\t0\t1\t100\t0\t0\t1e-10\n')
+    cols = {'qseqid': 0, 'staxids': 1, 'bitscore': 2, 'sseqid': 4,
+            'sstart': 9, 'send': 10, 'evalue': 13}
+    results = hits.parse_blast(str(blast), cols, accessions=taxdump.accessions,
+                               lineages=True)
+    assert taxdump.names[results['c1'][0]['taxid']] == 'Chordata'
+    assert taxdump.names[results['c2'][0]['taxid']] == 'Proteobacteria'
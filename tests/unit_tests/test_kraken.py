@@ -0,0 +1,32 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import kraken
+
+
+def test_parse_kraken_output(tmp_path):
+    kraken_file = tmp_path / 'assembly.kraken'
+    kraken_file.write_text(
+        'C\tc1\tEscherichia coli (taxid 562)\t5000\t562:10 0:5\n'
+        'U\tc2\tunclassified (taxid 0)\t5000\t0:15\n'
+        'C\tc3_-_2000\t2\t1000\t2:3\n')
+    file_format, results = kraken.parse_kraken_file(str(kraken_file))
+    assert file_format == 'kraken'
+    assert results['c1'][0]['taxid'] == 562
+    assert results['c1'][0]['score'] == 1
+    assert 'c2' not in results
+    assert results['c3'][0]['start'] == 2000
+
+
+def test_parse_centrifuge_output(tmp_path):
+    centrifuge_file = tmp_path / 'assembly.centrifuge'
+    centrifuge_file.write_text(
+        'readID\tseqID\ttaxID\tscore\t2ndBestScore\thitLength\tqueryLength\t'
+        'numMatches\n'
+        'c1\tNC_000913\t562\t900\t0\t80\t5000\t2\n'
+        'c1\tNC_002695\t83334\t300\t0\t40\t5000\t2\n'
+        'c2\tno rank\t0\t0\t0\t0\t5000\t1\n')
+    file_format, results = kraken.parse_kraken_file(str(centrifuge_file))
+    assert file_format == 'centrifuge'
+    assert [(hit['taxid'], hit['score']) for hit in results['c1']] == [
+        (562, 900), (83334, 300)]
+    assert 'c2' not in results
@@ -0,0 +1,33 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import Metadata, Taxdump, lca
+from lib.field import Identifier, Variable
+
+
+def test_parse_diamond_classification(tmp_path):
+    lca_file = tmp_path / 'diamond.tsv'
+    lca_file.write_text('c1\t4\t1e-50\nc1_-_1000\t7\t1e-20\nc2\t0\t0\n')
+    file_format, results = lca.parse_classification(str(lca_file))
+    assert file_format == 'diamond'
+    assert [hit['taxid'] for hit in results['c1']] == [4, 7]
+    assert results['c1'][1]['start'] == 1000
+    assert 'c2' not in results
+
+
+def test_parse_mmseqs_classification(tmp_path, _taxdump_dir):
+    lca_file = tmp_path / 'mmseqs_lca.tsv'
+    lca_file.write_text('c1\t4\tspecies\tEscherichia coli\n'
+                        'c2\t3\tphylum\tProteobacteria\n')
+    file_format, results = lca.parse_classification(str(lca_file))
+    assert file_format == 'mmseqs'
+    assert results['c1'][0]['subject'] == 'Escherichia coli'
+    dependencies = {'identifiers': Identifier('identifiers', values=['c1', 'c2']),
+                    'length': Variable('length', values=[5000, 5000])}
+    fields = lca.assign_classifications(
+        results, str(lca_file), 'mmseqs_lca', taxdump=Taxdump(_taxdump_dir),
+        dependencies=dependencies, meta=Metadata('test'))
+    fields = {field.field_id: field for field in fields}
+    assert fields['mmseqs_lca_phylum'].expand_values() == ['Proteobacteria',
+                                                           'Proteobacteria']
+    assert fields['mmseqs_lca_species'].expand_values() == [
+        'Escherichia coli', 'Proteobacteria-undef']
@@ -0,0 +1,30 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import repeatmasker
+from lib.field import Identifier
+
+OUT = '''   SW   perc perc perc  query  position in query    matching  repeat
+score   div. del. ins.  sequence    begin     end    (left)    repeat  class/family
+
+ 1000   10.0  0.0  0.0  c1        1     2000 (8000) +  L1      LINE/L1    1 2000 (0) 1
+  800   12.0  0.0  0.0  c1     1501     2500 (7500) +  AluY    SINE/Alu   1 1000 (0) 2
+  300    5.0  0.0  0.0  c1     5001     5500 (4500) +  (CA)n   Simple_repeat 1 500 (0) 3
+  200   20.0  0.0  0.0  c1     9001     9500  (500) C  rnd-1   Unknown   (0) 500 1 4
+'''
+
+
+def test_parse_repeatmasker(tmp_path):
+    rm_file = tmp_path / 'assembly.fa.out'
+    rm_file.write_text(OUT)
+    identifiers = Identifier('identifiers', values=['c1', 'c2'])
+    windows = [{'title': 'windows', 'key': '0.1', 'value': 0.1}]
+    fields = repeatmasker.parse_repeatmasker(str(rm_file), identifiers,
+                                             [10000, 5000], windows)
+    fields = {field.field_id: field for field in fields}
+    assert fields['repeats_fraction'].values == [0.35, 0]
+    assert fields['repeats_line_fraction'].values == [0.2, 0]
+    assert fields['repeats_sine_fraction'].values == [0.1, 0]
+    assert fields['repeats_simple_fraction'].values == [0.05, 0]
+    assert fields['repeats_ltr_fraction'].values == [0, 0]
+    repeats = fields['repeats_fraction_windows'].values[0]
+    assert [value[0] for value in repeats] == [1, 0.25, 0.25, 0, 0.25]
@@ -0,0 +1,32 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from docopt import docopt
+
+from lib import add, file_io, retaxrule
+
+
+def test_retaxrule_lca_to_bestsum(tmp_path, _taxdump_dir):
+    taxdump = _taxdump_dir
+    fasta = tmp_path / 'assembly.fa'
+    fasta.write_text('>c1\n%s\n>c2\n%s\n' % ('ACGT' * 500, 'ACGT' * 500))
+    blast = tmp_path / 'hits.tsv'
+    blast.write_text(
+        'c1\t4\t200\tx\ts1\t0\t0\t0\t0\t1\t100\t0\t0\t1e-10\n'
+        'c1\t7\t190\tx\ts2\t0\t0\t0\t0\t1\t100\t0\t0\t1e-10\n'
+        'c1\t4\t100\tx\ts3\t0\t0\t0\t0\t200\t300\t0\t0\t1e-10\n'
+        'c2\t7\t150\tx\ts4\t0\t0\t0\t0\t1\t100\t0\t0\t1e-10\n')
+    blobdir = str(tmp_path / 'BlobDir')
+    add.main(docopt(add.__doc__, argv=[
+        'add', '--fasta', str(fasta), '--hits', str(blast), '--taxrule', 'lca',
+        '--taxdump', taxdump, blobdir]))
+    phylum = file_io.load_yaml('%s/lca_phylum.json' % blobdir)
+    assert [phylum['keys'][value] for value in phylum['values']] == [
+        'unresolved', 'Chordata']
+    retaxrule.main(docopt(retaxrule.__doc__, argv=[
+        'retaxrule', '--taxdump', taxdump, '--source', 'lca', '--taxrule',
+        'bestsum', blobdir]))
+    phylum = file_io.load_yaml('%s/bestsum_phylum.json' % blobdir)
+    assert [phylum['keys'][value] for value in phylum['values']] == [
+        'Proteobacteria', 'Chordata']
+    score = file_io.load_yaml('%s/bestsum_phylum_score.json' % blobdir)
+    assert score['values'] == [300, 150]
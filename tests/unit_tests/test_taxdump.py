@@ -26,3 +26,21 @@ def my_taxdump():
 #     assert taxon['taxid'] == TAXON_ID
 #     assert taxon['superkingdom'] == DATASET_DATA['taxon']['superkingdom']
 #     assert taxon['common_name'] == DATASET_DATA['taxon']['common_name']
+
+
+def test_load_lineages(tmp_path):
+    lineage_file = tmp_path / 'lineages.tsv'
+    lineage_file.write_text(
+        'GB_GCA_1\td__Bacteria;p__Proteobacteria;c__Gammaproteobacteria\n'
+        'GB_GCA_2\td__Bacteria;p__Firmicutes\n'
+    )
+    taxdump = Taxdump(str(lineage_file))
+    taxid = taxdump.accessions['GB_GCA_1']
+    assert taxdump.ranks[taxid] == 'class'
+    assert taxdump.lineage(taxid) == {'superkingdom': 'Bacteria',
+                                      'phylum': 'Proteobacteria',
+                                      'class': 'Gammaproteobacteria'}
+    ancestors = taxdump.ancestors[taxdump.accessions['GB_GCA_2']]
+    assert taxdump.names[ancestors['phylum']] == 'Firmicutes'
+    assert ancestors['kingdom'] == -ancestors['superkingdom']
+    assert ancestors['class'] == -ancestors['phylum']
@@ -0,0 +1,24 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import telomere
+
+
+def test_telomere_length_clips_to_last_copy():
+    for tail in ('ACGTCATGCAATCG', 'TTAGCACCCCCCCC', 'TAGGCACACATGGT'):
+        assert telomere.telomere_length('TTAGGG' * 20 + tail, 'TTAGGG') == 120
+    assert telomere.telomere_length('CCCTAA' * 20 + 'GTCA', 'TTAGGG') == 120
+    assert telomere.telomere_length('ACGT' * 20, 'TTAGGG') == 0
+
+
+def test_telomere_length_accepts_single_mismatches():
+    seq_str = 'TTAGGG' * 10 + 'TTAGCG' + 'TTAGGG' * 5 + 'ACACACAC'
+    assert telomere.telomere_length(seq_str, 'TTAGGG') == 96
+
+
+def test_detect_motif_orientation(tmp_path):
+    fasta = tmp_path / 'assembly.fa'
+    fasta.write_text('>c1\n%s%s\n>c2\n%s%s\n' % (
+        'CCCTAA' * 30, 'ACGTTGCAAGTC' * 20, 'GATCCGATAGCA' * 20, 'TTAGGG' * 30))
+    assert telomere.detect_motif(str(fasta)) == 'TTAGGG'
+    assert telomere.conventional_motif('AACCCT') == 'TTAGGG'
+    assert telomere.conventional_motif('AGGTT') == 'TTAGG'
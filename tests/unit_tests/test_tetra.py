@@ -0,0 +1,28 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+from lib import tetra
+
+
+def test_reverse_complement():
+    assert tetra.reverse_complement('AACGTT') == 'AACGTT'
+    assert tetra.reverse_complement('TTAGGG') == 'CCCTAA'
+    assert len(tetra.KMERS) == 136
+
+
+def test_tetranucleotide_frequencies_merge_strands():
+    freqs = dict(zip(tetra.KMERS, tetra.tetranucleotide_frequencies('AAAATTTT')))
+    assert freqs['AAAA'] == 0.4
+    assert freqs['AAAT'] == 0.4
+    assert freqs['AATT'] == 0.2
+
+
+def test_tetra_pca_is_opt_in():
+    profiles = [tetra.tetranucleotide_frequencies(seq_str)
+                for seq_str in ('AAAATTTTGC' * 200, 'GCGCATAT' * 250, 'ACGT' * 500)]
+    fields = tetra.tetra_fields(profiles, [2000, 2000, 2000],
+                                **{'--tetra-components': None})
+    assert [field.field_id for field in fields] == ['tetra']
+    fields = tetra.tetra_fields(profiles, [2000, 2000, 2000],
+                                **{'--tetra-components': '2'})
+    assert [field.field_id for field in fields] == ['tetra', 'tetra_pc1',
+                                                    'tetra_pc2']
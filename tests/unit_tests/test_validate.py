@@ -0,0 +1,29 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+import pytest
+
+from lib import validate
+
+
+def confidence_field(**kwargs):
+    field = {'id': 'bestsum_phylum_confidence', 'type': 'variable',
+             'datatype': 'float', 'range': [0, 1]}
+    field.update(kwargs)
+    return field
+
+
+def test_confidence_field_is_valid():
+    validate.check_expected_field_properties(confidence_field())
+
+
+def test_confidence_field_range():
+    with pytest.raises(AssertionError):
+        validate.check_expected_field_properties(confidence_field(range=[0, 2]))
+
+
+def test_runnerup_field_is_category():
+    validate.check_expected_field_properties(
+        {'id': 'bestsum_phylum_runnerup', 'type': 'category'})
+    with pytest.raises(AssertionError):
+        validate.check_expected_field_properties(
+            {'id': 'bestsum_phylum_runnerup', 'type': 'variable'})
@@ -0,0 +1,47 @@
+#!/usr/bin/env python3
+# pylint: disable=wrong-import-position
+import pytest
+
+from lib import vcf
+from lib.field import Identifier
+
+VCF = '''##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ts1\ts2
+c1\t100\t.\tA\tG\t50\tPASS\t.\tGT:AD\t0/1:6,4\t0/0:10,0
+c1\t3000\t.\tC\tT\t50\tPASS\t.\tGT:AD\t0/1:5,5\t1/1:0,10
+c1\t5000\t.\tG\tA\t5\tLowQual\t.\tGT:AD\t0/1:5,5\t0/1:5,5
+c1\t7000\t.\tT\tC\t50\t.\t.\tGT\t1/1\t0/1
+c1\t9000\t.\tA\tT\t50\tPASS\t.\tGT\t./.\t0/0
+c2\t100\t.\tG\tC\t50\tPASS\t.\tGT:AD\t1|1:0,8\t.
+'''
+
+
+def parse(tmp_path, sample=None):
+    vcf_file = tmp_path / 'variants.vcf'
+    vcf_file.write_text(VCF)
+    identifiers = Identifier('identifiers', values=['c1', 'c2'])
+    windows = [{'title': 'windows', 'key': '0.1', 'value': 0.1}]
+    fields = vcf.parse_vcf(str(vcf_file), identifiers, [10000, 5000], windows,
+                           sample=sample)
+    return {field.field_id: field for field in fields}
+
+
+def test_parse_vcf(tmp_path):
+    fields = parse(tmp_path)
+    assert fields['vcf_het_count'].values == [2, 0]
+    assert fields['vcf_homalt_count'].values == [1, 1]
+    assert fields['vcf_het_density'].values == [0.2, 0]
+    assert fields['vcf_homalt_density'].values == [0.1, 0.2]
+    assert fields['vcf_allele_balance_mean'].values == [0.45, 0]
+    het = fields['vcf_het_count_windows'].values[0]
+    assert [value[0] for value in het] == [1, 1, 0, 0, 0]
+    assert 'vcf_allele_balance_mean_windows' not in fields
+
+
+def test_parse_vcf_sample(tmp_path):
+    fields = parse(tmp_path, sample='s2')
+    assert fields['vcf_het_count'].values == [1, 0]
+    assert fields['vcf_homalt_count'].values == [1, 0]
+    assert fields['vcf_het_count'].meta['sample'] == 's2'
+    with pytest.raises(UserWarning):
+        parse(tmp_path, sample='s3')